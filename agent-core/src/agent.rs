@@ -1,19 +1,56 @@
-use crate::event_log::{Event, EventLog, EventType};
+use crate::bdi::{
+    self, IncidentBeliefs, IncidentPlanner, NoPlan, OutcomeHistory, RemediateIncident,
+};
+use crate::event_log::{now_string, Event, EventLog, EventType};
+use crate::executor::{self, Halt, RevisionDecision};
 use crate::facts::Fact;
-use crate::llm::{self, LlmConfig};
+use crate::llm::LlmConfig;
 use crate::planner;
 use crate::rules;
-use crate::runbooks::{ActionSchema, Runbook};
-use crate::{executor, runbooks};
-use std::sync::mpsc::Receiver;
+use crate::runbooks::{self, ActionSchema, Runbook};
+use rig_bdi::{BeliefBase, Conflict, Desire, Environment, Intention, LinearNetwork, Outcome};
+use rig_planner::{PartialOrderPlan, WorldState};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::mpsc::{Receiver, Sender};
+
+pub use rig_bdi::{Commitment, Goal};
 
 #[derive(Clone)]
 pub struct AgentConfig {
     pub max_replan_attempts: usize,
     pub runbooks: Vec<(&'static str, Runbook)>,
     pub all_actions: Vec<ActionSchema>,
-    pub goal_props: Vec<String>,
+    /// Root goals; each alert's incident pursues the highest-priority one
+    /// that applies.
+    pub goals: Vec<Goal>,
     pub llm: Option<LlmConfig>,
+    /// When a running plan is reconsidered as new facts arrive.
+    pub commitment: Commitment,
+    /// Undo action per step name, run when a revision aborts a plan after
+    /// the step ran.
+    pub compensations: BTreeMap<String, ActionSchema>,
+}
+
+impl AgentConfig {
+    /// The built-in runbooks toward `recovery_verified`, reconsidered
+    /// single-mindedly with up to three replans, and `llm` to fall back on.
+    pub fn standard(llm: Option<LlmConfig>) -> Self {
+        Self {
+            max_replan_attempts: 3,
+            runbooks: vec![
+                ("crashloop_runbook", runbooks::crashloop_runbook()),
+                ("oomkill_runbook", runbooks::oomkill_runbook()),
+            ],
+            all_actions: vec![runbooks::crashloop_runbook(), runbooks::oomkill_runbook()]
+                .into_iter()
+                .flatten()
+                .collect(),
+            goals: vec![Goal::new("recovery_verified")],
+            llm,
+            commitment: Commitment::SingleMinded,
+            compensations: runbooks::compensations(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -23,90 +60,123 @@ pub struct EscalationRequest {
 }
 
 pub fn run_agent<F>(
-    webhook_stream: Receiver<Fact>,
+    mut webhook_stream: Receiver<Fact>,
     config: AgentConfig,
     log: EventLog,
-    escalation_tx: std::sync::mpsc::Sender<EscalationRequest>,
+    escalation_tx: Sender<EscalationRequest>,
     tool_executor: F,
 ) where
     F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
 {
     let fallback = known_actions(&config);
-    let mut recent_facts: Vec<Fact> = Vec::new();
-
-    while let Ok(fact) = webhook_stream.recv() {
-        let incident_id = incident_id_from_fact(&fact);
-        recent_facts.push(fact.clone());
-        if recent_facts.len() > 16 {
-            let _ = recent_facts.remove(0);
-        }
+    let history = OutcomeHistory::default();
+    let mut agent = rig_bdi::Agent::new(
+        IncidentBeliefs::default(),
+        LinearNetwork::new(vec![RemediateIncident::new(config.goals.clone())]),
+        IncidentPlanner {
+            config: &config,
+            log: &log,
+            history: &history,
+            fallback: fallback.clone(),
+        },
+    )
+    .with_commitment(config.commitment);
+    let mut env = IncidentEnvironment {
+        config: &config,
+        fallback: &fallback,
+        log: &log,
+        history: &history,
+        escalation_tx: &escalation_tx,
+        tool_executor: &tool_executor,
+        pursuits: BTreeMap::new(),
+        revised: BTreeMap::new(),
+        incidents_by_service: BTreeMap::new(),
+    };
+    agent.run(&mut webhook_stream, &mut env);
+}
 
-        let _ = log.append(&Event {
-            id: None,
-            incident_id: incident_id.clone(),
-            event_type: EventType::FactAsserted,
-            description: "fact asserted".into(),
-            details: serde_json::to_value(&fact).ok(),
-            timestamp: now_string(),
-        });
+/// Replanning state for one incident's intention.
+struct Pursuit {
+    /// Believed state at the first failure, advanced past each later one.
+    state: WorldState,
+    excluded: Vec<String>,
+    attempt: usize,
+}
 
-        let pattern = rules::detect_pattern(&fact);
-        let selection = planner::select_runbook(pattern, &config.runbooks);
+/// Runs intentions through the executor, revising them between steps when
+/// new facts arrive and replanning around failed steps until the replan
+/// budget runs out, and logs every decision.
+struct IncidentEnvironment<'a, F> {
+    config: &'a AgentConfig,
+    fallback: &'a [ActionSchema],
+    log: &'a EventLog,
+    history: &'a OutcomeHistory,
+    escalation_tx: &'a Sender<EscalationRequest>,
+    tool_executor: &'a F,
+    pursuits: BTreeMap<String, Pursuit>,
+    /// The latest fact perceived per incident since its last step.
+    revised: BTreeMap<String, String>,
+    /// Incidents per service, from the alerts perceived so far.
+    incidents_by_service: BTreeMap<String, BTreeSet<String>>,
+}
 
-        let selected = if let Some((runbook_name, runbook)) = selection {
-            let _ = log.append(&Event {
-                id: None,
-                incident_id: incident_id.clone(),
-                event_type: EventType::PlanSelected,
-                description: format!("selected runbook: {runbook_name}"),
-                details: serde_json::to_value(&runbook).ok(),
-                timestamp: now_string(),
-            });
-            runbook
-        } else if let Some(llm_cfg) = config.llm.as_ref() {
-            match llm::interpret(llm_cfg, &recent_facts)
-                .and_then(|interp| {
-                    llm::propose_and_validate(
-                        llm_cfg,
-                        &interp.hypothesis,
-                        &interp.goal,
-                        &interp.candidate_actions,
-                        &fallback,
-                    )
-                    .map(|actions| (interp, actions))
-                }) {
-                Ok((interp, actions)) if !actions.is_empty() => {
-                    let _ = log.append(&Event {
-                        id: None,
-                        incident_id: incident_id.clone(),
-                        event_type: EventType::PlanSelected,
-                        description: format!(
-                            "LLM-proposed plan: {} steps ({})",
-                            actions.len(),
-                            interp.hypothesis
-                        ),
-                        details: serde_json::to_value(&actions).ok(),
-                        timestamp: now_string(),
-                    });
-                    actions
-                }
-                Ok((_interp, _)) => {
-                    escalate_no_plan(&log, &escalation_tx, incident_id.clone(), "no valid llm plan");
-                    continue;
+impl<F> Environment<IncidentBeliefs, NoPlan> for IncidentEnvironment<'_, F>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    fn step(&mut self, beliefs: &IncidentBeliefs, intention: &mut Intention) -> Option<Outcome> {
+        let incident_id = intention.desire.scope.clone();
+        let labels = beliefs.beliefs().labels(&incident_id);
+        let steps: Vec<ActionSchema> = intention
+            .plan
+            .steps
+            .iter()
+            .map(|step| runbooks::bind_params(step, &labels))
+            .collect();
+        let plan = PartialOrderPlan::from_steps(&steps);
+        let mut done = intention.done().to_vec();
+        if let Some(fact_id) = self.revised.remove(&incident_id) {
+            let decision =
+                bdi::revision_for(beliefs, intention, &fact_id, &self.config.compensations);
+            let halt = executor::apply_revision(
+                self.log,
+                &incident_id,
+                &plan,
+                &mut done,
+                decision,
+                self.tool_executor,
+            );
+            for step in (0..done.len()).filter(|&i| done[i]) {
+                intention.complete(step);
+            }
+            match halt {
+                Some(Halt::Aborted(decision)) => {
+                    if beliefs.world_state(&incident_id).has("alert_resolved") {
+                        let _ = self.log.append(&Event {
+                            id: None,
+                            incident_id,
+                            event_type: EventType::Resolved,
+                            description: decision.reason.clone(),
+                            details: Some(serde_json::json!({ "fact_id": decision.fact_id })),
+                            timestamp: now_string(),
+                        });
+                    }
+                    return Some(Outcome::Dropped(decision.reason));
                 }
-                Err(_) => {
-                    escalate_no_plan(&log, &escalation_tx, incident_id.clone(), "no valid llm plan");
-                    continue;
+                Some(Halt::Replan(decision)) => {
+                    return self.replan_revised(beliefs, intention, decision);
                 }
+                Some(Halt::Completed) | None => {}
             }
-        } else {
-            escalate_no_plan(&log, &escalation_tx, incident_id.clone(), "no matching runbook");
-            continue;
-        };
-
-        match executor::execute_plan(&log, &incident_id, &selected, &tool_executor) {
-            Ok(()) => {
-                let _ = log.append(&Event {
+        }
+        let ran =
+            executor::execute_wave(self.log, &incident_id, &plan, &mut done, self.tool_executor);
+        for step in (0..done.len()).filter(|&i| done[i]) {
+            intention.complete(step);
+        }
+        let failure = match ran {
+            Ok(_) if intention.is_achieved() => {
+                let _ = self.log.append(&Event {
                     id: None,
                     incident_id,
                     event_type: EventType::Resolved,
@@ -114,26 +184,307 @@ pub fn run_agent<F>(
                     details: None,
                     timestamp: now_string(),
                 });
+                return Some(Outcome::Achieved);
             }
-            Err((failed_step, reason)) => {
-                let req = EscalationRequest {
-                    incident_id: incident_id.clone(),
-                    reason: reason.clone(),
-                };
-                let _ = escalation_tx.send(req);
-                let _ = log.append(&Event {
+            Ok(_) => return None,
+            Err(failure) => failure,
+        };
+
+        let pursuit = self
+            .pursuits
+            .entry(incident_id.clone())
+            .or_insert_with(|| Pursuit {
+                state: beliefs.world_state(&incident_id),
+                excluded: Vec::new(),
+                attempt: 0,
+            });
+        let failed_step = &steps[failure.index];
+        let completed: Vec<ActionSchema> = intention
+            .completed()
+            .into_iter()
+            .map(|i| steps[i].clone())
+            .collect();
+        pursuit.state = planner::state_after_failure(&pursuit.state, &completed, failed_step);
+        // A refused approval gate rules out the step it guards.
+        let ruled_out = rig_planner::gated_action(failed_step).unwrap_or(&failed_step.name);
+        if !pursuit.excluded.iter().any(|name| name == ruled_out) {
+            pursuit.excluded.push(ruled_out.to_string());
+        }
+
+        if pursuit.attempt >= self.config.max_replan_attempts {
+            let req = EscalationRequest {
+                incident_id: incident_id.clone(),
+                reason: failure.reason.clone(),
+            };
+            let _ = self.escalation_tx.send(req);
+            let _ = self.log.append(&Event {
+                id: None,
+                incident_id,
+                event_type: EventType::Escalated,
+                description: "escalation required".into(),
+                details: Some(serde_json::json!({
+                    "name": failed_step.name,
+                    "effect": format!("{:?}", failed_step.effect),
+                    "status": "failed",
+                    "reason": failure.reason,
+                    "replan_attempts": pursuit.attempt
+                })),
+                timestamp: now_string(),
+            });
+            return Some(Outcome::Dropped(failure.reason));
+        }
+        pursuit.attempt += 1;
+        let attempt = pursuit.attempt;
+
+        // Links the new plan back to the ActionResult that triggered it.
+        let replan = serde_json::json!({
+            "attempt": attempt,
+            "max_attempts": self.config.max_replan_attempts,
+            "failed_step": failed_step.name,
+            "failed_index": failure.index,
+            "reason": failure.reason,
+            "failure_event_id": failure.event_id,
+            "excluded_actions": pursuit.excluded,
+        });
+        let labels = beliefs.beliefs().labels(&incident_id);
+        match planner::replan(
+            &pursuit.state,
+            &intention.desire.goals,
+            self.fallback,
+            &pursuit.excluded,
+            &self.history.model(self.log),
+        ) {
+            Ok(plan) => {
+                let plan = runbooks::bind_plan(plan, &labels);
+                let _ = self.log.append(&Event {
                     id: None,
                     incident_id,
-                    event_type: EventType::Escalated,
-                    description: "escalation required".into(),
+                    event_type: EventType::PlanSelected,
+                    description: format!(
+                        "replanned after {} failed (attempt {attempt}/{}): {} steps (cost {})",
+                        failed_step.name,
+                        self.config.max_replan_attempts,
+                        plan.steps.len(),
+                        plan.total_cost
+                    ),
                     details: Some(serde_json::json!({
-                        "name": failed_step.name,
-                        "effect": format!("{:?}", failed_step.effect),
-                        "status": "failed",
-                        "reason": reason
+                        "steps": plan.steps,
+                        "replan": replan
                     })),
                     timestamp: now_string(),
                 });
+                intention.revise(plan);
+                None
+            }
+            Err(unreachable) => {
+                let reason = format!("replan unreachable after {} failed", failed_step.name);
+                escalate_no_plan(
+                    self.log,
+                    self.escalation_tx,
+                    incident_id,
+                    &reason,
+                    serde_json::to_value(&unreachable).ok(),
+                );
+                Some(Outcome::Dropped(reason))
+            }
+        }
+    }
+
+    /// An alert is asserted on its own incident; an infrastructure fact on
+    /// every incident alerting for its service, and on none until one does.
+    fn perceived(&mut self, fact: &Fact) {
+        for incident_id in self.incidents_for(fact) {
+            self.revised
+                .insert(incident_id.clone(), rig_rete::Fact::id(fact).clone());
+            let _ = self.log.append(&Event {
+                id: None,
+                incident_id,
+                event_type: EventType::FactAsserted,
+                description: "fact asserted".into(),
+                details: serde_json::to_value(fact).ok(),
+                timestamp: now_string(),
+            });
+        }
+    }
+
+    fn adopted(&mut self, intention: &Intention) {
+        // The plan was made from these facts; nothing to revise yet.
+        self.revised.remove(&intention.desire.scope);
+        let _ = self.log.append(&Event {
+            id: None,
+            incident_id: intention.desire.scope.clone(),
+            event_type: EventType::IntentionAdopted,
+            description: format!(
+                "intention adopted: {} steps toward {}",
+                intention.plan.steps.len(),
+                intention.desire.goals.join(", ")
+            ),
+            details: Some(serde_json::json!({
+                "desire": intention.desire,
+                "commitment": self.config.commitment,
+                "steps": intention.plan.steps.len(),
+            })),
+            timestamp: now_string(),
+        });
+    }
+
+    fn preempted(&mut self, intention: &Intention, by: &Intention) {
+        let _ = self.log.append(&Event {
+            id: None,
+            incident_id: intention.desire.scope.clone(),
+            event_type: EventType::IntentionPreempted,
+            description: format!(
+                "intention paused for higher-priority incident {}",
+                by.desire.scope
+            ),
+            details: Some(serde_json::json!({
+                "priority": intention.desire.priority,
+                "preempted_by": by.desire.scope,
+                "preempted_by_priority": by.desire.priority,
+                "completed_steps": intention.completed(),
+            })),
+            timestamp: now_string(),
+        });
+    }
+
+    fn ended(&mut self, intention: &Intention, outcome: &Outcome) {
+        self.pursuits.remove(&intention.desire.scope);
+        let Outcome::Dropped(reason) = outcome else {
+            return;
+        };
+        let (done, total) = intention.progress();
+        let _ = self.log.append(&Event {
+            id: None,
+            incident_id: intention.desire.scope.clone(),
+            event_type: EventType::IntentionDropped,
+            description: format!("intention dropped after {done}/{total} steps: {reason}"),
+            details: Some(serde_json::json!({
+                "desire": intention.desire,
+                "commitment": self.config.commitment,
+                "reason": reason,
+                "completed_steps": intention.completed(),
+            })),
+            timestamp: now_string(),
+        });
+    }
+
+    fn unplanned(&mut self, desire: &Desire, no_plan: NoPlan) {
+        escalate_no_plan(
+            self.log,
+            self.escalation_tx,
+            desire.scope.clone(),
+            &no_plan.reason,
+            no_plan.unreachable,
+        );
+    }
+
+    fn conflicted(&mut self, desire: &Desire, with: &Intention, conflicts: &[Conflict]) {
+        let incidents = [desire.scope.clone(), with.desire.scope.clone()];
+        for (incident_id, other) in [
+            (&incidents[0], &incidents[1]),
+            (&incidents[1], &incidents[0]),
+        ] {
+            let reason = format!("plan conflicts with incident {other}");
+            let _ = self.escalation_tx.send(EscalationRequest {
+                incident_id: incident_id.clone(),
+                reason: reason.clone(),
+            });
+            let _ = self.log.append(&Event {
+                id: None,
+                incident_id: incident_id.clone(),
+                event_type: EventType::Escalated,
+                description: "escalation required".into(),
+                details: Some(serde_json::json!({
+                    "status": "conflict",
+                    "reason": reason,
+                    "conflicting_incidents": incidents,
+                    "conflicts": conflicts,
+                })),
+                timestamp: now_string(),
+            });
+        }
+    }
+}
+
+impl<F> IncidentEnvironment<'_, F>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    /// The incidents `fact` bears on: an alert's own, recording the services
+    /// it names, or every incident on an infrastructure fact's service.
+    fn incidents_for(&mut self, fact: &Fact) -> Vec<String> {
+        let Fact::Alert(alert) = fact else {
+            return fact
+                .service()
+                .and_then(|service| self.incidents_by_service.get(service))
+                .map(|ids| ids.iter().cloned().collect())
+                .unwrap_or_default();
+        };
+        let beliefs = rules::derive_beliefs(std::slice::from_ref(fact));
+        for tuple in beliefs.query("service") {
+            if let Some(service) = tuple.get(1) {
+                self.incidents_by_service
+                    .entry(service.clone())
+                    .or_default()
+                    .insert(alert.id.clone());
+            }
+        }
+        vec![alert.id.clone()]
+    }
+
+    /// Plan again from the revised beliefs, past the steps already run.
+    fn replan_revised(
+        &mut self,
+        beliefs: &IncidentBeliefs,
+        intention: &mut Intention,
+        decision: RevisionDecision,
+    ) -> Option<Outcome> {
+        let incident_id = intention.desire.scope.clone();
+        let state = intention
+            .completed()
+            .into_iter()
+            .fold(beliefs.world_state(&incident_id), |state, i| {
+                state.apply(&intention.plan.steps[i])
+            });
+        let labels = beliefs.beliefs().labels(&incident_id);
+        match planner::replan(
+            &state,
+            &intention.desire.goals,
+            self.fallback,
+            &[],
+            &self.history.model(self.log),
+        ) {
+            Ok(plan) => {
+                let plan = runbooks::bind_plan(plan, &labels);
+                let _ = self.log.append(&Event {
+                    id: None,
+                    incident_id,
+                    event_type: EventType::PlanSelected,
+                    description: format!(
+                        "replanned after beliefs revised by {}: {} steps (cost {})",
+                        decision.fact_id,
+                        plan.steps.len(),
+                        plan.total_cost
+                    ),
+                    details: Some(serde_json::json!({
+                        "steps": plan.steps,
+                        "revision": decision
+                    })),
+                    timestamp: now_string(),
+                });
+                intention.revise(plan);
+                None
+            }
+            Err(unreachable) => {
+                let reason = format!("replan unreachable after {}", decision.reason);
+                escalate_no_plan(
+                    self.log,
+                    self.escalation_tx,
+                    incident_id,
+                    &reason,
+                    serde_json::to_value(&unreachable).ok(),
+                );
+                Some(Outcome::Dropped(reason))
             }
         }
     }
@@ -160,9 +511,10 @@ fn known_actions(config: &AgentConfig) -> Vec<ActionSchema> {
 
 fn escalate_no_plan(
     log: &EventLog,
-    escalation_tx: &std::sync::mpsc::Sender<EscalationRequest>,
+    escalation_tx: &Sender<EscalationRequest>,
     incident_id: String,
     reason: &str,
+    unreachable: Option<serde_json::Value>,
 ) {
     let req = EscalationRequest {
         incident_id: incident_id.clone(),
@@ -176,23 +528,339 @@ fn escalate_no_plan(
         description: "escalation required".into(),
         details: Some(serde_json::json!({
             "status": "failed",
-            "reason": reason
+            "reason": reason,
+            "unreachable": unreachable
         })),
         timestamp: now_string(),
     });
 }
 
-fn incident_id_from_fact(fact: &Fact) -> String {
-    match fact {
-        Fact::Alert(alert) => alert.id.clone(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facts::{AlertFact, AlertSource, Severity};
+    use rig_effects::Effect;
+
+    fn db_path(name: &str) -> String {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        format!("/tmp/rig-bdi-tests/{name}-{nanos}.db")
     }
-}
 
-fn now_string() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
+    fn alert(id: &str, severity: Severity) -> Fact {
+        Fact::Alert(AlertFact {
+            id: id.into(),
+            source: AlertSource::Generic,
+            severity,
+            title: "pod crashlooping".into(),
+            tags: Vec::new(),
+            received_at: "1".into(),
+            occurred_at: None,
+            labels: Default::default(),
+        })
+    }
 
-    let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) else {
-        return "0".into();
-    };
-    duration.as_secs().to_string()
+    fn run_once(path: &str, max_replan_attempts: usize) -> Vec<Event> {
+        run_alerts(
+            path,
+            max_replan_attempts,
+            Commitment::Blind,
+            alert("inc-replan", Severity::High),
+            None,
+            "rollback-deployment",
+        )
+    }
+
+    /// Run the agent on `first`, delivering `mid_plan` while the first tool
+    /// call runs, with `rejected` always failing. Returns every event.
+    fn run_alerts(
+        path: &str,
+        max_replan_attempts: usize,
+        commitment: Commitment,
+        first: Fact,
+        mid_plan: Option<Fact>,
+        rejected: &'static str,
+    ) -> Vec<Event> {
+        let mut all_actions = runbooks::crashloop_runbook();
+        all_actions.push(
+            ActionSchema::new("scale-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+        );
+        let config = AgentConfig {
+            max_replan_attempts,
+            runbooks: vec![("crashloop_runbook", runbooks::crashloop_runbook())],
+            all_actions,
+            goals: vec![Goal::new("recovery_verified")],
+            llm: None,
+            commitment,
+            compensations: runbooks::compensations(),
+        };
+
+        let (fact_tx, fact_rx) = std::sync::mpsc::channel();
+        let (escalation_tx, _escalation_rx) = std::sync::mpsc::channel();
+        fact_tx.send(first).expect("send");
+        // The stream closes once the mid-plan fact is sent.
+        let pending = std::sync::Mutex::new(mid_plan.map(|fact| (fact_tx, fact)));
+
+        let log = EventLog::open(path).expect("open");
+        run_agent(fact_rx, config, log, escalation_tx, move |action| {
+            if let Some((tx, fact)) = pending.lock().expect("lock").take() {
+                tx.send(fact).expect("send");
+            }
+            if action.name == rejected {
+                Err("rollback rejected".into())
+            } else {
+                Ok(serde_json::json!({"status": "ok"}))
+            }
+        });
+
+        EventLog::open(path)
+            .expect("reopen")
+            .events_after(0)
+            .expect("events")
+    }
+
+    #[test]
+    fn failed_step_is_replanned_around() {
+        let events = run_once(&db_path("agent-replan"), 3);
+
+        let failure_id = events
+            .iter()
+            .find(|e| e.description == "action failed: rollback-deployment")
+            .and_then(|e| e.id)
+            .expect("failure event");
+        let replan = events
+            .iter()
+            .filter(|e| matches!(e.event_type, EventType::PlanSelected))
+            .nth(1)
+            .and_then(|e| e.details.as_ref())
+            .expect("replan event");
+        assert_eq!(replan["replan"]["failure_event_id"], failure_id);
+        assert_eq!(replan["replan"]["attempt"], 1);
+        assert_eq!(replan["steps"][0]["name"], "scale-deployment");
+
+        assert!(matches!(
+            events.last().map(|e| &e.event_type),
+            Some(EventType::Resolved)
+        ));
+    }
+
+    #[test]
+    fn zero_replan_attempts_escalates_immediately() {
+        let events = run_once(&db_path("agent-no-replan"), 0);
+        let plans = events
+            .iter()
+            .filter(|e| matches!(e.event_type, EventType::PlanSelected))
+            .count();
+        assert_eq!(plans, 1);
+
+        let types: Vec<_> = events.iter().rev().take(2).map(|e| &e.event_type).collect();
+        assert!(matches!(
+            types[..],
+            [EventType::IntentionDropped, EventType::Escalated]
+        ));
+        let dropped = events
+            .last()
+            .and_then(|e| e.details.as_ref())
+            .expect("drop");
+        assert_eq!(dropped["reason"], "rollback rejected");
+    }
+
+    #[test]
+    fn open_minded_agent_readopts_when_severity_changes() {
+        let events = run_alerts(
+            &db_path("agent-open-minded"),
+            3,
+            Commitment::OpenMinded,
+            alert("inc-replan", Severity::High),
+            Some(alert("inc-replan", Severity::Critical)),
+            "none",
+        );
+
+        let adopted: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e.event_type, EventType::IntentionAdopted))
+            .filter_map(|e| e.details.as_ref())
+            .collect();
+        assert_eq!(adopted.len(), 2);
+        assert_eq!(adopted[0]["desire"]["priority"], 3);
+        assert_eq!(adopted[1]["desire"]["priority"], 4);
+
+        let dropped = events
+            .iter()
+            .find(|e| matches!(e.event_type, EventType::IntentionDropped))
+            .and_then(|e| e.details.as_ref())
+            .expect("drop");
+        assert_eq!(dropped["reason"], "desire changed");
+        assert_eq!(dropped["commitment"], "OpenMinded");
+        assert!(matches!(
+            events.last().map(|e| &e.event_type),
+            Some(EventType::Resolved)
+        ));
+    }
+
+    #[test]
+    fn resolution_mid_plan_aborts_remaining_steps() {
+        let Fact::Alert(firing) = alert("inc-replan", Severity::High) else {
+            unreachable!("alert() builds alerts");
+        };
+        let resolved = Fact::Alert(AlertFact {
+            tags: vec!["status:resolved".into()],
+            ..firing
+        });
+        let events = run_alerts(
+            &db_path("agent-resolved"),
+            3,
+            Commitment::Blind,
+            alert("inc-replan", Severity::High),
+            Some(resolved),
+            "none",
+        );
+
+        let revised = events
+            .iter()
+            .find(|e| matches!(e.event_type, EventType::PlanRevised))
+            .and_then(|e| e.details.as_ref())
+            .expect("revision");
+        assert_eq!(revised["fact_id"], "inc-replan");
+        assert_eq!(revised["revision"]["decision"], "abort");
+        assert_eq!(
+            revised["remaining_steps"],
+            serde_json::json!(["rollback-deployment", "verify-recovery"])
+        );
+        assert!(!events
+            .iter()
+            .any(|e| e.description == "intent: rollback-deployment"));
+
+        let types: Vec<_> = events.iter().rev().take(2).map(|e| &e.event_type).collect();
+        assert!(matches!(
+            types[..],
+            [EventType::IntentionDropped, EventType::Resolved]
+        ));
+        let dropped = events
+            .last()
+            .and_then(|e| e.details.as_ref())
+            .expect("drop");
+        assert_eq!(dropped["reason"], "alert resolved");
+    }
+
+    #[test]
+    fn conflicting_incidents_are_escalated_together() {
+        let config = AgentConfig {
+            max_replan_attempts: 3,
+            runbooks: Vec::new(),
+            all_actions: vec![
+                ActionSchema::new("scale-up-deployment", Effect::Mutate)
+                    .requires("crashloop_detected")
+                    .adds("capacity_raised")
+                    .adds("recovery_verified"),
+                ActionSchema::new("scale-down-deployment", Effect::Mutate)
+                    .requires("alert")
+                    .deletes("capacity_raised")
+                    .adds("cost_reduced"),
+            ],
+            goals: vec![
+                Goal::new("recovery_verified")
+                    .with_priority(1)
+                    .when("crashloop_detected"),
+                Goal::new("cost_reduced"),
+            ],
+            llm: None,
+            commitment: Commitment::Blind,
+            compensations: BTreeMap::new(),
+        };
+        let path = db_path("agent-conflict");
+        let (fact_tx, fact_rx) = std::sync::mpsc::channel();
+        let (escalation_tx, escalation_rx) = std::sync::mpsc::channel();
+        fact_tx
+            .send(alert("inc-recover", Severity::High))
+            .expect("send");
+        fact_tx
+            .send(Fact::Alert(AlertFact {
+                id: "inc-cost".into(),
+                source: AlertSource::Generic,
+                severity: Severity::Low,
+                title: "cluster underutilised".into(),
+                tags: Vec::new(),
+                received_at: "1".into(),
+                occurred_at: None,
+                labels: Default::default(),
+            }))
+            .expect("send");
+        drop(fact_tx);
+
+        let log = EventLog::open(&path).expect("open");
+        run_agent(fact_rx, config, log, escalation_tx, |_| {
+            panic!("conflicting plans must not run")
+        });
+
+        let escalated: Vec<_> = escalation_rx.iter().map(|r| r.incident_id).collect();
+        assert_eq!(escalated, vec!["inc-cost", "inc-recover"]);
+
+        let events = EventLog::open(&path)
+            .expect("reopen")
+            .events_after(0)
+            .expect("events");
+        let dropped = events
+            .iter()
+            .find(|e| matches!(e.event_type, EventType::IntentionDropped))
+            .expect("drop");
+        assert_eq!(dropped.incident_id, "inc-recover");
+        let escalation = events
+            .iter()
+            .rev()
+            .find(|e| matches!(e.event_type, EventType::Escalated))
+            .and_then(|e| e.details.as_ref())
+            .expect("escalation");
+        assert_eq!(escalation["conflicts"][0]["proposition"], "capacity_raised");
+        assert_eq!(
+            escalation["conflicting_incidents"],
+            serde_json::json!(["inc-cost", "inc-recover"])
+        );
+    }
+
+    #[test]
+    fn critical_incident_preempts_low_one_between_steps() {
+        let events = run_alerts(
+            &db_path("agent-preempt"),
+            3,
+            Commitment::Blind,
+            alert("inc-low", Severity::Low),
+            Some(alert("inc-critical", Severity::Critical)),
+            "none",
+        );
+
+        let preempted = events
+            .iter()
+            .find(|e| matches!(e.event_type, EventType::IntentionPreempted))
+            .expect("preemption");
+        assert_eq!(preempted.incident_id, "inc-low");
+        let details = preempted.details.as_ref().expect("details");
+        assert_eq!(details["preempted_by"], "inc-critical");
+        assert_eq!(details["completed_steps"], serde_json::json!([0]));
+
+        let resolved: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e.event_type, EventType::Resolved))
+            .map(|e| e.incident_id.as_str())
+            .collect();
+        assert_eq!(resolved, vec!["inc-critical", "inc-low"]);
+
+        // The low incident's last step before preemption had finished.
+        let before: Vec<_> = events
+            .iter()
+            .take_while(|e| e.id < preempted.id)
+            .filter(|e| e.incident_id == "inc-low")
+            .filter(|e| {
+                matches!(
+                    e.event_type,
+                    EventType::ActionIntent | EventType::ActionResult
+                )
+            })
+            .collect();
+        assert_eq!(before.len(), 2);
+    }
 }
@@ -0,0 +1,538 @@
+//! The incident agent as an instance of [`rig_bdi`]: Datalog beliefs over a
+//! window of recent facts, one remediation desire per alert toward the most
+//! pressing configured goal, and runbook planning with an LLM fallback.
+
+use crate::agent::AgentConfig;
+use crate::event_log::{now_string, Event, EventLog, EventType};
+use crate::executor::{Revision, RevisionDecision};
+use crate::facts::{AlertFact, Fact, Severity};
+use crate::llm;
+use crate::planner;
+use crate::rules::{self, Beliefs};
+use crate::runbooks::{self, ActionSchema};
+use rig_bdi::{BeliefBase, Desire, Goal, Intention, Planner};
+use rig_effects::Effect;
+use rig_planner::{OutcomeModel, Plan, WorldState};
+use rig_rete::{Condition, Rule};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// Plans ranked per incident; the runners-up are kept for the escalation panel.
+const PLAN_ALTERNATIVES: usize = 3;
+
+/// Facts kept for belief derivation and LLM context.
+const RECENT_FACTS: usize = 16;
+
+// ── Beliefs ──────────────────────────────────────────────────────────────────
+
+/// Beliefs derived from the most recent facts.
+#[derive(Clone, Debug, Default)]
+pub struct IncidentBeliefs {
+    recent_facts: Vec<Fact>,
+    beliefs: Beliefs,
+}
+
+impl IncidentBeliefs {
+    pub fn recent_facts(&self) -> &[Fact] {
+        &self.recent_facts
+    }
+
+    pub fn beliefs(&self) -> &Beliefs {
+        &self.beliefs
+    }
+}
+
+impl BeliefBase for IncidentBeliefs {
+    type Fact = Fact;
+
+    fn revise(&mut self, fact: &Fact) {
+        self.recent_facts.push(fact.clone());
+        if self.recent_facts.len() > RECENT_FACTS {
+            let _ = self.recent_facts.remove(0);
+        }
+        self.beliefs = rules::derive_beliefs(&self.recent_facts);
+    }
+
+    fn world_state(&self, scope: &str) -> WorldState {
+        planner::incident_state(&self.beliefs, scope)
+    }
+}
+
+// ── Desires ──────────────────────────────────────────────────────────────────
+
+struct IsAlert;
+
+impl Condition<Fact> for IsAlert {
+    type Bindings = Option<AlertFact>;
+
+    fn matches(&self, fact: &Fact) -> Option<Option<AlertFact>> {
+        match fact {
+            Fact::Alert(alert) => Some(Some(alert.clone())),
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> &str {
+        "fact is an alert"
+    }
+}
+
+/// Every alert raises a desire for its incident toward the highest-priority
+/// configured goal that applies to what the alert signals, as pressing as
+/// the alert is severe. Resolutions raise none: they revise the incident
+/// they resolve rather than open one.
+pub struct RemediateIncident {
+    goals: Vec<Goal>,
+    conditions: Vec<Box<dyn Condition<Fact, Bindings = Option<AlertFact>>>>,
+}
+
+impl RemediateIncident {
+    pub fn new(goals: Vec<Goal>) -> Self {
+        Self {
+            goals,
+            conditions: vec![Box::new(IsAlert)],
+        }
+    }
+}
+
+impl Clone for RemediateIncident {
+    fn clone(&self) -> Self {
+        Self::new(self.goals.clone())
+    }
+}
+
+impl Rule for RemediateIncident {
+    type Fact = Fact;
+    type Action = Desire;
+    type Bindings = Option<AlertFact>;
+
+    fn id(&self) -> &str {
+        "remediate-incident"
+    }
+
+    fn conditions(&self) -> &[Box<dyn Condition<Fact, Bindings = Option<AlertFact>>>] {
+        &self.conditions
+    }
+
+    fn actions(&self, bindings: &Option<AlertFact>) -> Vec<Desire> {
+        bindings
+            .iter()
+            .filter_map(|alert| {
+                let fact = Fact::Alert(alert.clone());
+                let signals = rules::derive_beliefs(std::slice::from_ref(&fact));
+                if signals.holds("alert_resolved", &[&alert.id]) {
+                    return None;
+                }
+                let goal =
+                    Goal::select(&self.goals, &planner::incident_state(&signals, &alert.id))?;
+                let raised_at = rig_rete::Fact::timestamp(&fact);
+                Some(
+                    Desire::for_goal(alert.id.clone(), goal, raised_at)
+                        .with_priority(severity_priority(&alert.severity)),
+                )
+            })
+            .collect()
+    }
+
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    fn description(&self) -> &str {
+        "remediate the incident an alert belongs to"
+    }
+}
+
+pub fn severity_priority(severity: &Severity) -> i32 {
+    match severity {
+        Severity::Low => 1,
+        Severity::Medium => 2,
+        Severity::High => 3,
+        Severity::Critical => 4,
+    }
+}
+
+// ── Planning ─────────────────────────────────────────────────────────────────
+
+/// Why an incident could not be planned for.
+#[derive(Clone, Debug)]
+pub struct NoPlan {
+    pub reason: String,
+    pub unreachable: Option<serde_json::Value>,
+}
+
+/// Plans from the matching runbook (or every known action), logging the
+/// chosen plan and its alternatives; falls back to an LLM-proposed plan when
+/// the goal is unreachable and an LLM is configured.
+pub struct IncidentPlanner<'a> {
+    pub config: &'a AgentConfig,
+    pub log: &'a EventLog,
+    pub history: &'a OutcomeHistory,
+    pub fallback: Vec<ActionSchema>,
+}
+
+impl Planner<IncidentBeliefs> for IncidentPlanner<'_> {
+    type Error = NoPlan;
+
+    fn plan(
+        &self,
+        beliefs: &IncidentBeliefs,
+        desire: &Desire,
+        done: &[ActionSchema],
+    ) -> Result<Plan, NoPlan> {
+        let incident_id = &desire.scope;
+        let labels = beliefs.beliefs.labels(incident_id);
+        let pattern = beliefs.beliefs.pattern(incident_id);
+        let (source, candidates) = match planner::select_runbook(pattern, &self.config.runbooks) {
+            Some((runbook_name, runbook)) => (runbook_name, runbook),
+            None => ("all_actions", self.fallback.clone()),
+        };
+        let planned = planner::alternatives_for_beliefs(
+            &beliefs.beliefs,
+            incident_id,
+            done,
+            &desire.goals,
+            &candidates,
+            PLAN_ALTERNATIVES,
+            &self.history.model(self.log),
+        );
+
+        let unreachable = match planned {
+            Ok(alternatives) => {
+                let plan = runbooks::bind_plan(alternatives[0].plan.clone(), &labels);
+                let _ = self.log.append(&Event {
+                    id: None,
+                    incident_id: incident_id.clone(),
+                    event_type: EventType::PlanSelected,
+                    description: format!(
+                        "planned from {source}: {} steps (cost {}), {} alternatives",
+                        plan.steps.len(),
+                        plan.total_cost,
+                        alternatives.len() - 1
+                    ),
+                    details: Some(serde_json::json!({
+                        "steps": plan.steps,
+                        "alternatives": alternatives
+                    })),
+                    timestamp: now_string(),
+                });
+                return Ok(plan);
+            }
+            Err(unreachable) => serde_json::to_value(&unreachable).ok(),
+        };
+
+        let Some(llm_cfg) = self.config.llm.as_ref() else {
+            return Err(NoPlan {
+                reason: "goal unreachable".into(),
+                unreachable,
+            });
+        };
+        match llm::interpret(llm_cfg, &beliefs.recent_facts, &beliefs.beliefs).and_then(|interp| {
+            llm::propose_and_validate(
+                llm_cfg,
+                &interp.hypothesis,
+                &interp.goal,
+                &interp.candidate_actions,
+                &self.fallback,
+            )
+            .map(|actions| (interp, actions))
+        }) {
+            Ok((interp, actions)) if !actions.is_empty() => {
+                let actions: Vec<ActionSchema> = rig_planner::shape_steps(&actions)
+                    .iter()
+                    .map(|step| runbooks::bind_params(step, &labels))
+                    .collect();
+                let _ = self.log.append(&Event {
+                    id: None,
+                    incident_id: incident_id.clone(),
+                    event_type: EventType::PlanSelected,
+                    description: format!(
+                        "LLM-proposed plan: {} steps ({})",
+                        actions.len(),
+                        interp.hypothesis
+                    ),
+                    details: serde_json::to_value(&actions).ok(),
+                    timestamp: now_string(),
+                });
+                Ok(Plan {
+                    total_cost: actions.iter().map(ActionSchema::cost).sum(),
+                    steps: actions,
+                })
+            }
+            Ok(_) | Err(_) => Err(NoPlan {
+                reason: "no valid llm plan".into(),
+                unreachable,
+            }),
+        }
+    }
+}
+
+// ── Belief revision ──────────────────────────────────────────────────────────
+
+/// How `intention` should go on after `fact_id` revised the beliefs about
+/// its incident: drop what is left once the alert is resolved, since the
+/// remediation worked; abort if the revised facts show a degradation the
+/// plan did not set out to address, undoing completed steps with their
+/// `compensations` in reverse order; skip what is left if the goals already
+/// hold; carry on while the remaining steps still reach them; otherwise
+/// replan.
+pub fn revision_for(
+    beliefs: &IncidentBeliefs,
+    intention: &Intention,
+    fact_id: &str,
+    compensations: &BTreeMap<String, ActionSchema>,
+) -> RevisionDecision {
+    let steps = &intention.plan.steps;
+    let believed = beliefs.world_state(&intention.desire.scope);
+    let decide = |revision, reason: String| RevisionDecision {
+        fact_id: fact_id.to_string(),
+        revision,
+        reason,
+    };
+    if believed.has("alert_resolved") {
+        return decide(
+            Revision::Abort {
+                compensate: Vec::new(),
+            },
+            "alert resolved".into(),
+        );
+    }
+
+    let worse = worsened(&believed, intention);
+    if !worse.is_empty() {
+        let labels = beliefs.beliefs().labels(&intention.desire.scope);
+        let compensate = intention
+            .completed()
+            .into_iter()
+            .rev()
+            .filter_map(|i| compensations.get(&steps[i].name))
+            .map(|undo| runbooks::bind_params(undo, &labels))
+            .collect();
+        return decide(
+            Revision::Abort { compensate },
+            format!("completed steps made things worse: {}", worse.join(", ")),
+        );
+    }
+
+    let state = intention
+        .completed()
+        .into_iter()
+        .fold(believed, |state, i| state.apply(&steps[i]));
+    if state.satisfies(&intention.desire.goals) {
+        let steps = (0..steps.len()).filter(|&i| !intention.done()[i]).collect();
+        return decide(Revision::Skip { steps }, "goals already hold".into());
+    }
+    let sim = rig_planner::simulate(&state, &intention.remaining(), &intention.desire.goals);
+    if sim.succeeds() {
+        decide(
+            Revision::Continue,
+            "remaining steps still reach the goals".into(),
+        )
+    } else {
+        decide(
+            Revision::Replan,
+            "remaining steps no longer reach the goals".into(),
+        )
+    }
+}
+
+/// Signals of a degrading service, as derived per incident.
+const DEGRADATIONS: &[&str] = &[
+    "crashloop_detected",
+    "oomkill_detected",
+    "pods_unready",
+    "deployment_degraded",
+    "threshold_breached",
+    "errors_logged",
+];
+
+/// Degradations `believed` holds that no step of the plan requires or
+/// removes, so were not there to address when a Mutate step ran; empty
+/// until one has.
+fn worsened(believed: &WorldState, intention: &Intention) -> Vec<String> {
+    let steps = &intention.plan.steps;
+    let mutated = intention
+        .completed()
+        .into_iter()
+        .any(|i| steps[i].effect == Effect::Mutate);
+    if !mutated {
+        return Vec::new();
+    }
+    DEGRADATIONS
+        .iter()
+        .filter(|signal| believed.has(signal))
+        .filter(|signal| {
+            !steps.iter().any(|step| {
+                step.preconditions.iter().any(|p| p == *signal)
+                    || step.delete_effects.iter().any(|p| p == *signal)
+            })
+        })
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Action success rates across every incident in the log, read once and
+/// then kept up to date from the events appended since.
+#[derive(Debug, Default)]
+pub struct OutcomeHistory {
+    /// The last event id counted, and the counts so far.
+    seen: RefCell<(i64, OutcomeModel)>,
+}
+
+impl OutcomeHistory {
+    pub fn model(&self, log: &EventLog) -> OutcomeModel {
+        let mut seen = self.seen.borrow_mut();
+        let (last_id, model) = &mut *seen;
+        if let Ok(events) = log.events_after(*last_id) {
+            planner::record_outcomes(model, &events);
+            *last_id = events.iter().filter_map(|e| e.id).fold(*last_id, i64::max);
+        }
+        model.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facts::AlertSource;
+    use rig_bdi::LinearNetwork;
+    use rig_rete::ReteNetwork;
+
+    #[test]
+    fn alert_raises_desire_ranked_by_severity() {
+        let mut network = LinearNetwork::new(vec![RemediateIncident::new(vec![
+            Goal::new("acknowledged").when("oomkill_detected"),
+            Goal::new("recovery_verified")
+                .when("crashloop_detected")
+                .within(600)
+                .subgoal(Goal::new("remediation_applied")),
+        ])]);
+        let fact = Fact::Alert(AlertFact {
+            id: "inc-bdi".into(),
+            source: AlertSource::Generic,
+            severity: Severity::Critical,
+            title: "pod crashlooping".into(),
+            tags: Vec::new(),
+            received_at: "1".into(),
+            occurred_at: None,
+            labels: Default::default(),
+        });
+
+        let desires: Vec<Desire> = network
+            .on_assert(&fact)
+            .iter()
+            .flat_map(|m| m.rule.actions(&m.bindings))
+            .collect();
+        assert_eq!(desires.len(), 1);
+        assert_eq!(desires[0].scope, "inc-bdi");
+        assert_eq!(desires[0].priority, 4);
+        assert_eq!(
+            desires[0].goals,
+            vec!["remediation_applied", "recovery_verified"]
+        );
+        assert_eq!(desires[0].deadline.map(|d| d.timestamp()), Some(601));
+
+        let mut beliefs = IncidentBeliefs::default();
+        beliefs.revise(&fact);
+        assert!(beliefs.world_state("inc-bdi").has("crashloop_detected"));
+
+        let Fact::Alert(firing) = fact else {
+            unreachable!()
+        };
+        let resolved = Fact::Alert(AlertFact {
+            tags: vec!["status:resolved".into()],
+            ..firing
+        });
+        assert!(network
+            .on_assert(&resolved)
+            .iter()
+            .all(|m| m.rule.actions(&m.bindings).is_empty()));
+    }
+
+    #[test]
+    fn resolution_keeps_the_fix_and_degradation_undoes_it() {
+        let alert = |tags: &[&str]| {
+            Fact::Alert(AlertFact {
+                id: "inc-rev".into(),
+                source: AlertSource::Generic,
+                severity: Severity::High,
+                title: "checkout crashlooping".into(),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                received_at: "1".into(),
+                occurred_at: None,
+                labels: Default::default(),
+            })
+        };
+        let mut intention = Intention::new(
+            Desire::new("inc-rev", vec!["recovery_verified".into()]),
+            Plan {
+                steps: runbooks::crashloop_runbook(),
+                total_cost: 0,
+            },
+        );
+        intention.complete(0);
+        intention.complete(1);
+        let revise = |fact: Fact| {
+            let mut beliefs = IncidentBeliefs::default();
+            beliefs.revise(&alert(&["service:checkout"]));
+            beliefs.revise(&fact);
+            revision_for(&beliefs, &intention, "f", &runbooks::compensations())
+        };
+
+        let resolved = revise(alert(&["service:checkout", "status:resolved"]));
+        assert_eq!(
+            resolved.revision,
+            Revision::Abort {
+                compensate: Vec::new()
+            }
+        );
+
+        let oom = revise(Fact::Pod(crate::facts::PodFact {
+            id: "pod/shop/checkout-1".into(),
+            name: "checkout-1".into(),
+            namespace: "shop".into(),
+            service: "checkout".into(),
+            phase: "running".into(),
+            restarts: 3,
+            ready: true,
+            reason: Some("OOMKilled".into()),
+            observed_at: "1".into(),
+        }));
+        let Revision::Abort { compensate } = oom.revision else {
+            panic!("expected an abort, got {:?}", oom.revision);
+        };
+        assert_eq!(compensate[0].name, "roll-forward-deployment");
+        assert!(oom.reason.contains("oomkill_detected"));
+    }
+
+    #[test]
+    fn outcome_history_counts_each_result_once() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let log = EventLog::open(&format!("/tmp/rig-bdi-tests/outcomes-{nanos}.db")).expect("open");
+        let record = |status: &str| {
+            log.append(&Event {
+                id: None,
+                incident_id: "inc-1".into(),
+                event_type: EventType::ActionResult,
+                description: String::new(),
+                details: Some(serde_json::json!({"name": "rollback-deployment", "status": status})),
+                timestamp: "1".into(),
+            })
+            .expect("append");
+        };
+        let history = OutcomeHistory::default();
+
+        record("done");
+        record("failed");
+        assert_eq!(
+            history.model(&log).counts("rollback-deployment").failures,
+            1
+        );
+        record("failed");
+        let counts = history.model(&log).counts("rollback-deployment");
+        assert_eq!((counts.successes, counts.failures), (1, 2));
+    }
+}
@@ -0,0 +1,266 @@
+//! Minimal stratified Datalog evaluator.
+//!
+//! Relations are sets of string tuples. A [`Program`] is a list of strata;
+//! each stratum is evaluated to a fixpoint before the next one starts, so a
+//! rule may only negate relations that are fully computed in earlier strata.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub type Tuple = Vec<String>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Var(String),
+    Const(String),
+}
+
+pub fn var(name: &str) -> Term {
+    Term::Var(name.into())
+}
+
+pub fn val(value: &str) -> Term {
+    Term::Const(value.into())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Atom {
+    pub relation: String,
+    pub terms: Vec<Term>,
+}
+
+pub fn atom(relation: &str, terms: Vec<Term>) -> Atom {
+    Atom {
+        relation: relation.into(),
+        terms,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Literal {
+    /// Joins against every tuple of the relation.
+    Pos(Atom),
+    /// Holds when no tuple matches. All variables must already be bound.
+    Neg(Atom),
+    /// Holds when both (bound) terms differ.
+    Neq(Term, Term),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub head: Atom,
+    pub body: Vec<Literal>,
+}
+
+pub fn rule(head: Atom, body: Vec<Literal>) -> Rule {
+    Rule { head, body }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Database {
+    relations: BTreeMap<String, BTreeSet<Tuple>>,
+}
+
+impl Database {
+    /// Insert a tuple. Returns `true` if it was not already present.
+    pub fn insert(&mut self, relation: &str, tuple: Tuple) -> bool {
+        self.relations
+            .entry(relation.to_string())
+            .or_default()
+            .insert(tuple)
+    }
+
+    pub fn contains(&self, relation: &str, tuple: &[String]) -> bool {
+        self.relations
+            .get(relation)
+            .is_some_and(|tuples| tuples.contains(tuple))
+    }
+
+    pub fn tuples(&self, relation: &str) -> impl Iterator<Item = &Tuple> {
+        self.relations.get(relation).into_iter().flatten()
+    }
+
+    /// Names of relations holding at least one tuple.
+    pub fn relation_names(&self) -> impl Iterator<Item = &str> {
+        self.relations
+            .iter()
+            .filter(|(_, tuples)| !tuples.is_empty())
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Program {
+    strata: Vec<Vec<Rule>>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stratum(mut self, rules: Vec<Rule>) -> Self {
+        self.strata.push(rules);
+        self
+    }
+
+    /// Evaluate every stratum to a fixpoint, adding derived tuples to `db`.
+    pub fn run(&self, db: &mut Database) {
+        for rules in &self.strata {
+            loop {
+                let mut derived = Vec::new();
+                for rule in rules {
+                    for bindings in solve(db, &rule.body) {
+                        if let Some(tuple) = ground(&rule.head, &bindings) {
+                            derived.push((rule.head.relation.as_str(), tuple));
+                        }
+                    }
+                }
+
+                let mut changed = false;
+                for (relation, tuple) in derived {
+                    changed |= db.insert(relation, tuple);
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+type Bindings = BTreeMap<String, String>;
+
+fn solve(db: &Database, body: &[Literal]) -> Vec<Bindings> {
+    let mut frontier = vec![Bindings::new()];
+
+    for literal in body {
+        let mut next = Vec::new();
+        for bindings in frontier {
+            match literal {
+                Literal::Pos(atom) => {
+                    for tuple in db.tuples(&atom.relation) {
+                        if let Some(extended) = unify(atom, tuple, &bindings) {
+                            next.push(extended);
+                        }
+                    }
+                }
+                Literal::Neg(atom) => {
+                    if let Some(tuple) = ground(atom, &bindings) {
+                        if !db.contains(&atom.relation, &tuple) {
+                            next.push(bindings);
+                        }
+                    }
+                }
+                Literal::Neq(left, right) => {
+                    if let (Some(l), Some(r)) =
+                        (resolve(left, &bindings), resolve(right, &bindings))
+                    {
+                        if l != r {
+                            next.push(bindings);
+                        }
+                    }
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    frontier
+}
+
+fn unify(atom: &Atom, tuple: &[String], bindings: &Bindings) -> Option<Bindings> {
+    if atom.terms.len() != tuple.len() {
+        return None;
+    }
+
+    let mut out = bindings.clone();
+    for (term, value) in atom.terms.iter().zip(tuple) {
+        match term {
+            Term::Const(c) if c != value => return None,
+            Term::Const(_) => {}
+            Term::Var(name) => match out.get(name) {
+                Some(bound) if bound != value => return None,
+                Some(_) => {}
+                None => {
+                    out.insert(name.clone(), value.clone());
+                }
+            },
+        }
+    }
+    Some(out)
+}
+
+fn resolve(term: &Term, bindings: &Bindings) -> Option<String> {
+    match term {
+        Term::Const(c) => Some(c.clone()),
+        Term::Var(name) => bindings.get(name).cloned(),
+    }
+}
+
+fn ground(atom: &Atom, bindings: &Bindings) -> Option<Tuple> {
+    atom.terms.iter().map(|t| resolve(t, bindings)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(db: &mut Database, from: &str, to: &str) {
+        db.insert("edge", vec![from.into(), to.into()]);
+    }
+
+    #[test]
+    fn computes_transitive_closure() {
+        let mut db = Database::default();
+        edge(&mut db, "a", "b");
+        edge(&mut db, "b", "c");
+        edge(&mut db, "c", "d");
+
+        Program::new()
+            .stratum(vec![
+                rule(
+                    atom("path", vec![var("x"), var("y")]),
+                    vec![Literal::Pos(atom("edge", vec![var("x"), var("y")]))],
+                ),
+                rule(
+                    atom("path", vec![var("x"), var("z")]),
+                    vec![
+                        Literal::Pos(atom("path", vec![var("x"), var("y")])),
+                        Literal::Pos(atom("edge", vec![var("y"), var("z")])),
+                    ],
+                ),
+            ])
+            .run(&mut db);
+
+        assert!(db.contains("path", &["a".into(), "d".into()]));
+        assert!(!db.contains("path", &["d".into(), "a".into()]));
+        assert_eq!(db.tuples("path").count(), 6);
+    }
+
+    #[test]
+    fn negation_reads_previous_stratum() {
+        let mut db = Database::default();
+        edge(&mut db, "a", "b");
+        db.insert("node", vec!["a".into()]);
+        db.insert("node", vec!["b".into()]);
+
+        Program::new()
+            .stratum(vec![rule(
+                atom("has_out", vec![var("x")]),
+                vec![Literal::Pos(atom("edge", vec![var("x"), var("_y")]))],
+            )])
+            .stratum(vec![rule(
+                atom("sink", vec![var("x")]),
+                vec![
+                    Literal::Pos(atom("node", vec![var("x")])),
+                    Literal::Neg(atom("has_out", vec![var("x")])),
+                ],
+            )])
+            .run(&mut db);
+
+        let sinks: Vec<_> = db.tuples("sink").cloned().collect();
+        assert_eq!(sinks, vec![vec!["b".to_string()]]);
+    }
+}
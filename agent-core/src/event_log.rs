@@ -1,3 +1,4 @@
+use fact_registry::SchemaRegistry;
 use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
@@ -11,6 +12,10 @@ pub enum EventType {
     FactSuggested,
     FactSuggestionResolved,
     PlanSelected,
+    IntentionAdopted,
+    IntentionDropped,
+    IntentionPreempted,
+    PlanRevised,
     ActionIntent,
     ActionResult,
     Escalated,
@@ -28,9 +33,22 @@ pub struct Event {
     pub timestamp: String,
 }
 
+/// The current time in epoch seconds, as events record it.
+pub(crate) fn now_string() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return "0".into();
+    };
+    duration.as_secs().to_string()
+}
+
 #[derive(Clone)]
 pub struct EventLog {
     db_path: Arc<PathBuf>,
+    /// Upgrades canonical payloads in `details` to their latest version as
+    /// they are read back, so older events read like new ones.
+    registry: Option<Arc<SchemaRegistry>>,
 }
 
 impl EventLog {
@@ -63,9 +81,16 @@ impl EventLog {
 
         Ok(Self {
             db_path: Arc::new(db_path),
+            registry: None,
         })
     }
 
+    /// Read canonical payloads back upgraded through `registry`.
+    pub fn with_registry(mut self, registry: SchemaRegistry) -> Self {
+        self.registry = Some(Arc::new(registry));
+        self
+    }
+
     pub fn append(&self, event: &Event) -> Result<i64, String> {
         let conn = Connection::open(&*self.db_path).map_err(|e| e.to_string())?;
         let event_type = serde_json::to_string(&event.event_type).map_err(|e| e.to_string())?;
@@ -108,7 +133,7 @@ impl EventLog {
 
         let mut events = Vec::new();
         for row in rows {
-            events.push(row.map_err(|e| e.to_string())?);
+            events.push(self.upgraded(row.map_err(|e| e.to_string())?));
         }
         Ok(events)
     }
@@ -130,7 +155,7 @@ impl EventLog {
 
         let mut events = Vec::new();
         for row in rows {
-            events.push(row.map_err(|e| e.to_string())?);
+            events.push(self.upgraded(row.map_err(|e| e.to_string())?));
         }
         Ok(events)
     }
@@ -199,6 +224,28 @@ impl EventLog {
     }
 }
 
+impl EventLog {
+    /// `event` with any canonical payload in its details (one declaring a
+    /// `schema`) at the latest version. Payloads were validated when they
+    /// were stored, so one the registry no longer reads is left as it is.
+    fn upgraded(&self, mut event: Event) -> Event {
+        let Some(registry) = &self.registry else {
+            return event;
+        };
+        if let Some(details) = event.details.take() {
+            event.details = Some(if details.get("schema").is_some() {
+                registry
+                    .upgrade(details.clone())
+                    .map(|upgraded| upgraded.value)
+                    .unwrap_or(details)
+            } else {
+                details
+            });
+        }
+        event
+    }
+}
+
 fn map_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Event> {
     let event_type_str: String = row.get(2)?;
     let details_str: Option<String> = row.get(4)?;
@@ -324,4 +371,44 @@ mod tests {
         let active = log.active_incidents().expect("active");
         assert_eq!(active, vec!["inc-2".to_string()]);
     }
+
+    #[test]
+    fn stored_canonical_payloads_are_read_at_the_latest_version() {
+        let path = db_path("upgrade-on-read");
+        let v1 = serde_json::json!({
+            "schema": "alert.v1",
+            "id": "a-1",
+            "source": "datadog",
+            "severity": "critical",
+            "title": "High error rate",
+            "tags": ["service:api"],
+            "occurred_at": "2024-05-01T12:00:00Z"
+        });
+        EventLog::open(&path)
+            .expect("open")
+            .append(&Event {
+                id: None,
+                incident_id: "inc-a".into(),
+                event_type: EventType::FactAsserted,
+                description: "fact".into(),
+                details: Some(v1.clone()),
+                timestamp: "1".into(),
+            })
+            .expect("append");
+
+        let raw = EventLog::open(&path).expect("open");
+        let stored = raw.events_for_incident("inc-a").expect("events");
+        assert_eq!(stored[0].details, Some(v1));
+
+        let log = raw.with_registry(fact_registry::canonical_registry());
+        for events in [
+            log.events_for_incident("inc-a").expect("events"),
+            log.events_after(0).expect("events after"),
+        ] {
+            let details = events[0].details.as_ref().expect("details");
+            assert_eq!(details["schema"], "alert.v3");
+            assert_eq!(details["status"], "firing");
+            assert_eq!(details["received_at"], details["occurred_at"]);
+        }
+    }
 }
@@ -1,95 +1,337 @@
-use crate::event_log::{Event, EventLog, EventType};
+use crate::event_log::{now_string, Event, EventLog, EventType};
 use crate::runbooks::ActionSchema;
+use rig_effects::Effect;
+use rig_planner::{gated_action, PartialOrderPlan};
+use serde::{Deserialize, Serialize};
 
+/// Where and why a plan stopped.
+#[derive(Clone, Debug)]
+pub struct StepFailure {
+    pub index: usize,
+    pub reason: String,
+    /// Id of the failed `ActionResult` event, when it was logged.
+    pub event_id: Option<i64>,
+    /// Indices of the steps that finished before execution stopped.
+    pub completed: Vec<usize>,
+}
+
+/// What to do with the rest of a plan once beliefs change mid-execution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "decision")]
+pub enum Revision {
+    /// Carry on as planned.
+    Continue,
+    /// Mark these steps done without running them.
+    Skip { steps: Vec<usize> },
+    /// Stop, first running `compensate` in order to undo what already ran.
+    Abort { compensate: Vec<ActionSchema> },
+    /// Stop so the caller can plan again from the revised beliefs.
+    Replan,
+}
+
+/// A [`Revision`], the fact that prompted it and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionDecision {
+    pub fact_id: String,
+    pub revision: Revision,
+    pub reason: String,
+}
+
+/// Why a revisable run stopped without a step failing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Halt {
+    /// Every step is done.
+    Completed,
+    /// A revision aborted the plan.
+    Aborted(RevisionDecision),
+    /// A revision asked for a new plan.
+    Replan(RevisionDecision),
+}
+
+/// Run `steps` as a [`PartialOrderPlan`], so independent reads overlap.
 pub fn execute_plan<F>(
     log: &EventLog,
     incident_id: &str,
     steps: &[ActionSchema],
     tool_executor: &F,
-) -> Result<(), (ActionSchema, String)>
+) -> Result<(), StepFailure>
 where
     F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
 {
-    for step in steps {
-        let _ = log.append(&Event {
-            id: None,
-            incident_id: incident_id.to_string(),
-            event_type: EventType::ActionIntent,
-            description: format!("intent: {}", step.name),
-            details: Some(serde_json::json!({
-                "name": step.name,
-                "effect": format!("{:?}", step.effect),
-                "status": "running",
-                "mcp": {
-                    "tool_name": step.name,
-                    "phase": "intent",
-                    "request": {
-                        "name": step.name,
-                        "effect": format!("{:?}", step.effect),
-                    }
-                }
-            })),
-            timestamp: now_string(),
-        });
+    execute_partial_order(
+        log,
+        incident_id,
+        &PartialOrderPlan::from_steps(steps),
+        tool_executor,
+    )
+}
+
+/// Run a DAG of steps in waves (see [`execute_wave`]) until every step is
+/// done or one fails.
+pub fn execute_partial_order<F>(
+    log: &EventLog,
+    incident_id: &str,
+    plan: &PartialOrderPlan,
+    tool_executor: &F,
+) -> Result<(), StepFailure>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    execute_revisable(log, incident_id, plan, tool_executor, &mut |_, _| None).map(|_| ())
+}
+
+/// Run a DAG of steps in waves like [`execute_partial_order`], asking
+/// `revise` before every wave whether revised beliefs change what is left.
+/// `None` carries on without logging anything; a decision is applied with
+/// [`apply_revision`].
+pub fn execute_revisable<F, R>(
+    log: &EventLog,
+    incident_id: &str,
+    plan: &PartialOrderPlan,
+    tool_executor: &F,
+    revise: &mut R,
+) -> Result<Halt, StepFailure>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+    R: FnMut(&PartialOrderPlan, &[bool]) -> Option<RevisionDecision>,
+{
+    let mut done = vec![false; plan.steps.len()];
+    loop {
+        if let Some(decision) = revise(plan, &done) {
+            let halt = apply_revision(log, incident_id, plan, &mut done, decision, tool_executor);
+            if let Some(halt) = halt {
+                return Ok(halt);
+            }
+        }
+        if !execute_wave(log, incident_id, plan, &mut done, tool_executor)? {
+            return Ok(Halt::Completed);
+        }
+    }
+}
 
-        match tool_executor(step.clone()) {
-            Ok(output) => {
-                let _ = log.append(&Event {
-                    id: None,
-                    incident_id: incident_id.to_string(),
-                    event_type: EventType::ActionResult,
-                    description: format!("action succeeded: {}", step.name),
-                    details: Some(serde_json::json!({
-                        "name": step.name,
-                        "effect": format!("{:?}", step.effect),
-                        "status": "done",
-                        "result": output,
-                        "mcp": {
-                            "tool_name": step.name,
-                            "phase": "result",
-                            "ok": true,
-                            "output": output
-                        }
-                    })),
-                    timestamp: now_string(),
-                });
+/// Log `decision` against the steps still left in `plan`, then apply it:
+/// skipped steps are marked done, and an abort runs its compensating steps
+/// one at a time, logged like any other step. Returns the [`Halt`] for an
+/// abort or replan, and `None` to carry on.
+pub fn apply_revision<F>(
+    log: &EventLog,
+    incident_id: &str,
+    plan: &PartialOrderPlan,
+    done: &mut [bool],
+    decision: RevisionDecision,
+    tool_executor: &F,
+) -> Option<Halt>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    let remaining: Vec<&str> = (0..plan.steps.len())
+        .filter(|&i| !done[i])
+        .map(|i| plan.steps[i].name.as_str())
+        .collect();
+    let _ = log.append(&Event {
+        id: None,
+        incident_id: incident_id.to_string(),
+        event_type: EventType::PlanRevised,
+        description: format!(
+            "beliefs revised by {}: {}",
+            decision.fact_id, decision.reason
+        ),
+        details: Some(serde_json::json!({
+            "fact_id": decision.fact_id,
+            "reason": decision.reason,
+            "revision": decision.revision,
+            "remaining_steps": remaining,
+        })),
+        timestamp: now_string(),
+    });
+
+    match &decision.revision {
+        Revision::Continue => None,
+        Revision::Skip { steps } => {
+            for &i in steps {
+                if let Some(skipped) = done.get_mut(i) {
+                    *skipped = true;
+                }
             }
+            None
+        }
+        Revision::Abort { compensate } => {
+            for step in compensate {
+                log_intent(log, incident_id, step);
+                let outcome = tool_executor(step.clone());
+                log_result(log, incident_id, step, &outcome);
+            }
+            Some(Halt::Aborted(decision))
+        }
+        Revision::Replan => Some(Halt::Replan(decision)),
+    }
+}
+
+/// Run the next wave of `plan`, marking the steps that finish in `done`.
+/// Returns `Ok(false)` when no step was left to run.
+///
+/// A wave is either every ready Pure/Observe step, run concurrently, or
+/// else the single earliest ready step, run alone, so Mutate and
+/// Irreversible steps never overlap anything. Its intents are logged before
+/// it starts and its results after it ends, both in step order.
+pub fn execute_wave<F>(
+    log: &EventLog,
+    incident_id: &str,
+    plan: &PartialOrderPlan,
+    done: &mut [bool],
+    tool_executor: &F,
+) -> Result<bool, StepFailure>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    let steps = &plan.steps;
+    let ready = plan.ready(done);
+    let Some(&first) = ready.first() else {
+        return Ok(false);
+    };
+    let concurrent: Vec<usize> = ready
+        .into_iter()
+        .filter(|&i| matches!(steps[i].effect, Effect::Pure | Effect::Observe))
+        // A gate runs alone, just before its step, never alongside reads.
+        .filter(|&i| gated_action(&steps[i]).is_none())
+        .collect();
+    let wave = if concurrent.is_empty() {
+        vec![first]
+    } else {
+        concurrent
+    };
+
+    for &i in &wave {
+        log_intent(log, incident_id, &steps[i]);
+    }
+    let outcomes = run_wave(steps, &wave, tool_executor);
+
+    let mut failure: Option<(usize, String, Option<i64>)> = None;
+    for (&i, outcome) in wave.iter().zip(outcomes) {
+        let event_id = log_result(log, incident_id, &steps[i], &outcome);
+        match outcome {
+            Ok(_) => done[i] = true,
             Err(err) => {
-                let err_msg = err.clone();
-                let _ = log.append(&Event {
-                    id: None,
-                    incident_id: incident_id.to_string(),
-                    event_type: EventType::ActionResult,
-                    description: format!("action failed: {}", step.name),
-                    details: Some(serde_json::json!({
-                        "name": step.name,
-                        "effect": format!("{:?}", step.effect),
-                        "status": "failed",
-                        "error": err_msg.clone(),
-                        "mcp": {
-                            "tool_name": step.name,
-                            "phase": "result",
-                            "ok": false,
-                            "error": err_msg
-                        }
-                    })),
-                    timestamp: now_string(),
-                });
-                return Err((step.clone(), err));
+                if failure.is_none() {
+                    failure = Some((i, err, event_id));
+                }
             }
         }
     }
 
-    Ok(())
+    match failure {
+        Some((index, reason, event_id)) => Err(StepFailure {
+            index,
+            reason,
+            event_id,
+            completed: (0..steps.len()).filter(|&i| done[i]).collect(),
+        }),
+        None => Ok(true),
+    }
+}
+
+fn run_wave<F>(
+    steps: &[ActionSchema],
+    wave: &[usize],
+    tool_executor: &F,
+) -> Vec<Result<serde_json::Value, String>>
+where
+    F: Fn(ActionSchema) -> Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    if let [only] = wave {
+        return vec![tool_executor(steps[*only].clone())];
+    }
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = wave
+            .iter()
+            .map(|&i| {
+                let step = steps[i].clone();
+                scope.spawn(move || tool_executor(step))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err("tool executor panicked".to_string()))
+            })
+            .collect()
+    })
 }
 
-fn now_string() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
+fn log_intent(log: &EventLog, incident_id: &str, step: &ActionSchema) {
+    let _ = log.append(&Event {
+        id: None,
+        incident_id: incident_id.to_string(),
+        event_type: EventType::ActionIntent,
+        description: format!("intent: {}", step.name),
+        details: Some(serde_json::json!({
+            "name": step.name,
+            "effect": format!("{:?}", step.effect),
+            "status": "running",
+            "mcp": {
+                "tool_name": step.name,
+                "phase": "intent",
+                "request": {
+                    "name": step.name,
+                    "effect": format!("{:?}", step.effect),
+                    "arguments": step.params,
+                }
+            }
+        })),
+        timestamp: now_string(),
+    });
+}
 
-    let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) else {
-        return "0".into();
+fn log_result(
+    log: &EventLog,
+    incident_id: &str,
+    step: &ActionSchema,
+    outcome: &Result<serde_json::Value, String>,
+) -> Option<i64> {
+    let event = match outcome {
+        Ok(output) => Event {
+            id: None,
+            incident_id: incident_id.to_string(),
+            event_type: EventType::ActionResult,
+            description: format!("action succeeded: {}", step.name),
+            details: Some(serde_json::json!({
+                "name": step.name,
+                "effect": format!("{:?}", step.effect),
+                "status": "done",
+                "result": output,
+                "mcp": {
+                    "tool_name": step.name,
+                    "phase": "result",
+                    "ok": true,
+                    "output": output
+                }
+            })),
+            timestamp: now_string(),
+        },
+        Err(err) => Event {
+            id: None,
+            incident_id: incident_id.to_string(),
+            event_type: EventType::ActionResult,
+            description: format!("action failed: {}", step.name),
+            details: Some(serde_json::json!({
+                "name": step.name,
+                "effect": format!("{:?}", step.effect),
+                "status": "failed",
+                "error": err,
+                "mcp": {
+                    "tool_name": step.name,
+                    "phase": "result",
+                    "ok": false,
+                    "error": err
+                }
+            })),
+            timestamp: now_string(),
+        },
     };
-    duration.as_secs().to_string()
+    log.append(&event).ok()
 }
 
 #[cfg(test)]
@@ -110,17 +352,17 @@ mod tests {
     #[test]
     fn failure_emits_action_result_with_failed_status() {
         let log = EventLog::open(&db_path("executor-failure")).expect("open");
-        let steps = vec![ActionSchema {
-            name: "failing-action".into(),
-            effect: Effect::Mutate,
-        }];
+        let steps = vec![ActionSchema::new("failing-action", Effect::Mutate)];
 
         let result = execute_plan(&log, "inc-fail", &steps, &|_step| {
             Err("boom".to_string())
         });
-        assert!(result.is_err());
+        let failure = result.expect_err("step should fail");
+        assert_eq!(failure.index, 0);
+        assert_eq!(failure.reason, "boom");
 
         let events = log.events_for_incident("inc-fail").expect("events");
+        assert_eq!(failure.event_id, events[1].id);
         assert_eq!(events.len(), 2);
         assert!(matches!(events[0].event_type, EventType::ActionIntent));
         assert!(matches!(events[1].event_type, EventType::ActionResult));
@@ -135,4 +377,149 @@ mod tests {
             Some("boom")
         );
     }
+
+    #[test]
+    fn independent_observes_overlap_and_mutates_run_alone() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let log = EventLog::open(&db_path("executor-parallel")).expect("open");
+        let steps = vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("inspect-memory-metrics", Effect::Observe)
+                .adds("has_memory_metrics"),
+            ActionSchema::new("tune-memory-limits", Effect::Mutate)
+                .requires("has_memory_metrics")
+                .adds("remediation_applied"),
+        ];
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak_observe = Arc::new(AtomicUsize::new(0));
+        let mutate_overlap = Arc::new(AtomicUsize::new(0));
+        let (flight, peak, overlap) = (
+            in_flight.clone(),
+            peak_observe.clone(),
+            mutate_overlap.clone(),
+        );
+        let result = execute_plan(&log, "inc-par", &steps, &move |step| {
+            let running = flight.fetch_add(1, Ordering::SeqCst) + 1;
+            if step.effect == Effect::Mutate {
+                overlap.fetch_max(running - 1, Ordering::SeqCst);
+            } else {
+                peak.fetch_max(running, Ordering::SeqCst);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(serde_json::json!({"status": "ok"}))
+        });
+        assert!(result.is_ok());
+        assert_eq!(peak_observe.load(Ordering::SeqCst), 2);
+        assert_eq!(mutate_overlap.load(Ordering::SeqCst), 0);
+
+        let events = log.events_for_incident("inc-par").expect("events");
+        let position = |kind: &str, name: &str| {
+            events
+                .iter()
+                .position(|e| e.description == format!("{kind}: {name}"))
+                .expect("event")
+        };
+        for step in &steps {
+            assert!(position("intent", &step.name) < position("action succeeded", &step.name));
+        }
+        assert!(
+            position("action succeeded", "inspect-memory-metrics")
+                < position("intent", "tune-memory-limits")
+        );
+    }
+
+    #[test]
+    fn revision_aborts_with_compensation_before_the_next_mutate() {
+        let log = EventLog::open(&db_path("executor-revision")).expect("open");
+        let steps = vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("scale-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("scaled"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("remediation_applied")
+                .adds("recovery_verified"),
+        ];
+        let undo = ActionSchema::new("restore-replicas", Effect::Mutate);
+
+        let halt = execute_revisable(
+            &log,
+            "inc-revise",
+            &PartialOrderPlan::from_steps(&steps),
+            &|step: ActionSchema| Ok(serde_json::json!({"ran": step.name})),
+            &mut |_, done: &[bool]| {
+                let revision = match done {
+                    [true, false, false, _] => Revision::Skip { steps: vec![1] },
+                    [true, true, true, false] => Revision::Abort {
+                        compensate: vec![undo.clone()],
+                    },
+                    _ => return None,
+                };
+                Some(RevisionDecision {
+                    fact_id: "inc-revise".into(),
+                    revision,
+                    reason: "alert resolved".into(),
+                })
+            },
+        )
+        .expect("no step fails");
+        assert!(matches!(halt, Halt::Aborted(ref d) if d.reason == "alert resolved"));
+
+        let events = log.events_for_incident("inc-revise").expect("events");
+        let names: Vec<&str> = events.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "intent: inspect-pod-logs",
+                "action succeeded: inspect-pod-logs",
+                "beliefs revised by inc-revise: alert resolved",
+                "intent: rollback-deployment",
+                "action succeeded: rollback-deployment",
+                "beliefs revised by inc-revise: alert resolved",
+                "intent: restore-replicas",
+                "action succeeded: restore-replicas",
+            ]
+        );
+        let skip = events[2].details.as_ref().expect("details");
+        assert_eq!(skip["revision"]["decision"], "skip");
+        let abort = events[5].details.as_ref().expect("details");
+        assert_eq!(abort["revision"]["decision"], "abort");
+        assert_eq!(
+            abort["remaining_steps"],
+            serde_json::json!(["verify-recovery"])
+        );
+    }
+
+    #[test]
+    fn failure_reports_completed_steps() {
+        let log = EventLog::open(&db_path("executor-completed")).expect("open");
+        let steps = vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("inspect-pod-events", Effect::Observe).adds("has_pod_events"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+        ];
+
+        let failure = execute_plan(&log, "inc-completed", &steps, &|step| {
+            if step.name == "inspect-pod-events" {
+                Err("forbidden".to_string())
+            } else {
+                Ok(serde_json::json!({"status": "ok"}))
+            }
+        })
+        .expect_err("events step fails");
+        assert_eq!(failure.index, 1);
+        assert_eq!(failure.completed, vec![0]);
+
+        let events = log.events_for_incident("inc-completed").expect("events");
+        assert!(!events.iter().any(|e| e.description == "intent: rollback-deployment"));
+    }
 }
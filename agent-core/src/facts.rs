@@ -1,4 +1,7 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Severity {
@@ -13,6 +16,8 @@ pub enum AlertSource {
     Generic,
     Datadog,
     PagerDuty,
+    /// A source read through a field mapping, by the mapping's name.
+    Mapped(String),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -22,10 +27,177 @@ pub struct AlertFact {
     pub severity: Severity,
     pub title: String,
     pub tags: Vec<String>,
+    /// When the alert was ingested, as epoch seconds.
     pub received_at: String,
+    /// When the alert fired at its source, if the source said.
+    #[serde(default)]
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// Normalized key-value labels, such as `namespace` and `deployment`.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// A pod's state as last observed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PodFact {
+    /// Working-memory key, `pod/<namespace>/<name>`.
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub service: String,
+    pub phase: String,
+    pub restarts: u32,
+    pub ready: bool,
+    pub reason: Option<String>,
+    pub observed_at: String,
+}
+
+/// A deployment's rollout state as last observed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DeployFact {
+    /// Working-memory key, `deploy/<namespace>/<name>`.
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub service: String,
+    pub version: String,
+    pub replicas: u32,
+    pub available_replicas: u32,
+    pub observed_at: String,
+}
+
+/// One sample of a service metric. Values are finite, so equality and
+/// hashing go by their bits.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricFact {
+    /// Working-memory key, `metric/<service>/<name>`, so a newer sample replaces the older one.
+    pub id: String,
+    pub service: String,
+    pub name: String,
+    pub value: f64,
+    pub unit: Option<String>,
+    pub threshold: Option<f64>,
+    pub observed_at: String,
+}
+
+impl MetricFact {
+    /// Whether the value is above its threshold.
+    pub fn breached(&self) -> bool {
+        self.threshold
+            .is_some_and(|threshold| self.value > threshold)
+    }
+
+    fn key(&self) -> (&str, &str, &str, u64, &Option<String>, Option<u64>, &str) {
+        (
+            &self.id,
+            &self.service,
+            &self.name,
+            self.value.to_bits(),
+            &self.unit,
+            self.threshold.map(f64::to_bits),
+            &self.observed_at,
+        )
+    }
+}
+
+impl PartialEq for MetricFact {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for MetricFact {}
+
+impl Hash for MetricFact {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// A log line worth keeping as a fact.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LogFact {
+    /// Working-memory key, such as `log/<service>/<observed_at>/<digest>`,
+    /// unique per line even within a second.
+    pub id: String,
+    pub service: String,
+    pub level: String,
+    pub message: String,
+    pub observed_at: String,
+}
+
+/// A change made to a service, such as a deploy or a config push.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChangeFact {
+    pub id: String,
+    pub service: String,
+    pub kind: String,
+    pub description: String,
+    pub author: Option<String>,
+    pub occurred_at: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Fact {
     Alert(AlertFact),
+    Pod(PodFact),
+    Deploy(DeployFact),
+    Metric(MetricFact),
+    Log(LogFact),
+    Change(ChangeFact),
+}
+
+impl Fact {
+    /// The working-memory key; see [`rig_rete::Fact::id`].
+    pub fn fact_id(&self) -> &str {
+        rig_rete::Fact::id(self)
+    }
+
+    /// The service an infrastructure fact describes; alerts name theirs in
+    /// tags, which the rules read.
+    pub fn service(&self) -> Option<&str> {
+        match self {
+            Fact::Alert(_) => None,
+            Fact::Pod(pod) => Some(&pod.service),
+            Fact::Deploy(deploy) => Some(&deploy.service),
+            Fact::Metric(metric) => Some(&metric.service),
+            Fact::Log(log) => Some(&log.service),
+            Fact::Change(change) => Some(&change.service),
+        }
+    }
+}
+
+impl rig_rete::Fact for Fact {
+    /// The incident ID for alerts, so a newer alert for the incident
+    /// replaces the older one. Infrastructure facts are keyed by what they
+    /// describe, such as `pod/<namespace>/<name>`.
+    type Id = String;
+
+    fn id(&self) -> &String {
+        match self {
+            Fact::Alert(alert) => &alert.id,
+            Fact::Pod(pod) => &pod.id,
+            Fact::Deploy(deploy) => &deploy.id,
+            Fact::Metric(metric) => &metric.id,
+            Fact::Log(log) => &log.id,
+            Fact::Change(change) => &change.id,
+        }
+    }
+
+    /// `received_at`, `observed_at` or `occurred_at` as epoch seconds;
+    /// anything else reads as the epoch.
+    fn timestamp(&self) -> DateTime<Utc> {
+        let secs = match self {
+            Fact::Alert(alert) => &alert.received_at,
+            Fact::Pod(pod) => &pod.observed_at,
+            Fact::Deploy(deploy) => &deploy.observed_at,
+            Fact::Metric(metric) => &metric.observed_at,
+            Fact::Log(log) => &log.observed_at,
+            Fact::Change(change) => &change.occurred_at,
+        };
+        secs.parse()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .unwrap_or_default()
+    }
 }
@@ -1,4 +1,6 @@
 pub mod agent;
+pub mod bdi;
+pub mod datalog;
 pub mod event_log;
 pub mod executor;
 pub mod facts;
@@ -1,4 +1,5 @@
 use crate::facts::Fact;
+use crate::rules::Beliefs;
 use crate::runbooks::ActionSchema;
 use futures::executor::block_on;
 use rig::client::ProviderClient;
@@ -45,13 +46,15 @@ pub struct SuggestedFact {
 pub fn interpret(
     config: &LlmConfig,
     recent_facts: &[Fact],
+    beliefs: &Beliefs,
 ) -> Result<Interpretation, String> {
     let facts_json = serde_json::to_string_pretty(recent_facts).map_err(|e| e.to_string())?;
+    let beliefs_json = serde_json::to_string_pretty(beliefs).map_err(|e| e.to_string())?;
     let extracted: Interpretation = run_extract(
         config,
-        "You are an incident interpreter. Infer likely incident hypothesis, goal, and candidate actions from facts.",
+        "You are an incident interpreter. Infer likely incident hypothesis, goal, and candidate actions from facts and derived beliefs.",
         "Analyze the incident facts and return a structured interpretation.",
-        &[("facts_json", facts_json)],
+        &[("facts_json", facts_json), ("derived_beliefs_json", beliefs_json)],
     )?;
 
     Ok(Interpretation {
@@ -1,5 +1,10 @@
-use crate::rules::IncidentPattern;
-use crate::runbooks::Runbook;
+use crate::event_log::{Event, EventType};
+use crate::rules::{Beliefs, IncidentPattern};
+use crate::runbooks::{ActionSchema, Runbook};
+use rig_planner::{
+    Alternative, DecompositionFailure, HtnDomain, OutcomeModel, Plan, Task, Unreachable,
+    WorldState,
+};
 
 pub fn select_runbook(
     pattern: IncidentPattern,
@@ -20,3 +25,276 @@ pub fn select_runbook(
 
     None
 }
+
+/// Plan from the propositions that hold for `incident_id` toward `goal_props`,
+/// shaped so reads run first and non-backtrackable steps are gated.
+pub fn plan_for_beliefs(
+    beliefs: &Beliefs,
+    incident_id: &str,
+    goal_props: &[String],
+    actions: &[ActionSchema],
+) -> Result<Plan, Unreachable> {
+    rig_planner::plan(&incident_state(beliefs, incident_id), goal_props, actions)
+        .map(|plan| rig_planner::shape(&plan))
+}
+
+/// Up to `k` shaped plans for `incident_id`, planned on from the effects of
+/// the `done` steps, best first, each compared with the best. The best
+/// minimizes expected cost under `model`; the rest are the cheapest other
+/// plans by effect cost.
+pub fn alternatives_for_beliefs(
+    beliefs: &Beliefs,
+    incident_id: &str,
+    done: &[ActionSchema],
+    goal_props: &[String],
+    actions: &[ActionSchema],
+    k: usize,
+    model: &OutcomeModel,
+) -> Result<Vec<Alternative>, Unreachable> {
+    let initial = done
+        .iter()
+        .fold(incident_state(beliefs, incident_id), |s, step| {
+            s.apply(step)
+        });
+    let best = rig_planner::plan_expected(&initial, goal_props, actions, model)?.plan;
+
+    let mut plans = vec![best];
+    for candidate in rig_planner::k_best(&initial, goal_props, actions, k)? {
+        if plans.len() >= k {
+            break;
+        }
+        if candidate.steps != plans[0].steps {
+            plans.push(candidate);
+        }
+    }
+
+    let shaped: Vec<Plan> = plans.iter().map(rig_planner::shape).collect();
+    Ok(rig_planner::compare(&shaped, model))
+}
+
+/// Success and failure counts per action, from every `ActionResult` in `events`.
+pub fn outcome_model(events: &[Event]) -> OutcomeModel {
+    let mut model = OutcomeModel::new();
+    record_outcomes(&mut model, events);
+    model
+}
+
+/// Add the counts from every `ActionResult` in `events` to `model`.
+pub fn record_outcomes(model: &mut OutcomeModel, events: &[Event]) {
+    for event in events {
+        if !matches!(event.event_type, EventType::ActionResult) {
+            continue;
+        }
+        let Some(details) = &event.details else {
+            continue;
+        };
+        let name = details.get("name").and_then(serde_json::Value::as_str);
+        let status = details.get("status").and_then(serde_json::Value::as_str);
+        match (name, status) {
+            (Some(name), Some("done")) => model.record(name, true),
+            (Some(name), Some("failed")) => model.record(name, false),
+            _ => {}
+        }
+    }
+}
+
+/// The propositions that hold for `incident_id`, as a planning state.
+pub fn incident_state(beliefs: &Beliefs, incident_id: &str) -> WorldState {
+    beliefs.propositions_for(incident_id).into_iter().collect()
+}
+
+/// Planning state after a partial run: `state` with each completed step's
+/// effects applied, plus a `failed:<name>` proposition for the failed step.
+pub fn state_after_failure(
+    state: &WorldState,
+    completed: &[ActionSchema],
+    failed: &ActionSchema,
+) -> WorldState {
+    let mut next = completed.iter().fold(state.clone(), |s, step| s.apply(step));
+    next.insert(format!("failed:{}", failed.name));
+    next
+}
+
+/// Shaped, expected-cost plan from `state` without any action named in
+/// `excluded`.
+pub fn replan(
+    state: &WorldState,
+    goal_props: &[String],
+    actions: &[ActionSchema],
+    excluded: &[String],
+    model: &OutcomeModel,
+) -> Result<Plan, Unreachable> {
+    let remaining: Vec<ActionSchema> = actions
+        .iter()
+        .filter(|a| !excluded.contains(&a.name))
+        .cloned()
+        .collect();
+    rig_planner::plan_expected(state, goal_props, &remaining, model)
+        .map(|expected| rig_planner::shape(&expected.plan))
+}
+
+/// Decompose compound `task` from the propositions that hold for `incident_id`.
+pub fn decompose_for_beliefs(
+    beliefs: &Beliefs,
+    incident_id: &str,
+    domain: &HtnDomain,
+    task: &str,
+) -> Result<Plan, DecompositionFailure> {
+    domain.decompose(&incident_state(beliefs, incident_id), &Task::compound(task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facts::{AlertFact, AlertSource, Fact, Severity};
+    use crate::rules;
+    use crate::runbooks;
+
+    fn alert(id: &str, title: &str, tags: &[&str]) -> Fact {
+        Fact::Alert(AlertFact {
+            id: id.into(),
+            source: AlertSource::Generic,
+            severity: Severity::High,
+            title: title.into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            received_at: "1".into(),
+            occurred_at: None,
+            labels: Default::default(),
+        })
+    }
+
+    fn step_names(plan: &Plan) -> Vec<&str> {
+        plan.steps.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn crashloop_network_rolls_back_when_deploy_correlates() {
+        let beliefs = rules::derive_beliefs(&[alert("inc-1", "crashloop after deploy", &[])]);
+        let plan = decompose_for_beliefs(
+            &beliefs,
+            "inc-1",
+            &runbooks::crashloop_task_network(),
+            runbooks::MITIGATE_CRASHLOOP,
+        )
+        .expect("plan");
+
+        assert_eq!(
+            step_names(&plan),
+            vec!["inspect-pod-logs", "inspect-pod-events", "rollback-deployment", "verify-recovery"]
+        );
+    }
+
+    #[test]
+    fn crashloop_network_falls_back_to_scaling() {
+        let beliefs = rules::derive_beliefs(&[alert("inc-2", "pod crashlooping", &[])]);
+        let plan = decompose_for_beliefs(
+            &beliefs,
+            "inc-2",
+            &runbooks::crashloop_task_network(),
+            runbooks::MITIGATE_CRASHLOOP,
+        )
+        .expect("plan");
+
+        assert_eq!(plan.steps[2].name, "scale-deployment");
+    }
+
+    #[test]
+    fn alternatives_rank_runbooks_against_best() {
+        let beliefs = rules::derive_beliefs(&[alert("inc-4", "pod crashlooping", &[])]);
+        let mut actions = runbooks::crashloop_runbook();
+        actions.push(
+            ActionSchema::new("delete-pod", rig_effects::Effect::Irreversible)
+                .requires("crashloop_detected")
+                .adds("remediation_applied"),
+        );
+
+        let alternatives = alternatives_for_beliefs(
+            &beliefs,
+            "inc-4",
+            &[],
+            &["recovery_verified".to_string()],
+            &actions,
+            2,
+            &OutcomeModel::new(),
+        )
+        .expect("alternatives");
+        assert_eq!(alternatives.len(), 2);
+        assert_eq!(alternatives[0].plan.steps[1].name, "rollback-deployment");
+
+        let b = &alternatives[1];
+        assert_eq!(b.irreversible_steps, 1);
+        assert_eq!(
+            step_names(&b.plan),
+            vec!["approve:delete-pod", "delete-pod", "verify-recovery"]
+        );
+        assert!(b.diff.cost_delta > 0);
+    }
+
+    #[test]
+    fn history_of_failures_steers_away_from_rollback() {
+        let result = |name: &str, status: &str| Event {
+            id: None,
+            incident_id: "old".into(),
+            event_type: EventType::ActionResult,
+            description: String::new(),
+            details: Some(serde_json::json!({"name": name, "status": status})),
+            timestamp: "1".into(),
+        };
+        let events = vec![
+            result("rollback-deployment", "failed"),
+            result("rollback-deployment", "failed"),
+            result("rollback-deployment", "done"),
+            result("rollback-deployment", "failed"),
+        ];
+        let model = outcome_model(&events);
+        assert_eq!(model.counts("rollback-deployment").failures, 3);
+
+        let beliefs = rules::derive_beliefs(&[alert("inc-5", "pod crashlooping", &[])]);
+        let mut actions = runbooks::crashloop_runbook();
+        actions.push(
+            ActionSchema::new("scale-deployment", rig_effects::Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+        );
+        let alternatives = alternatives_for_beliefs(
+            &beliefs,
+            "inc-5",
+            &[],
+            &["recovery_verified".to_string()],
+            &actions,
+            2,
+            &model,
+        )
+        .expect("alternatives");
+
+        assert_eq!(alternatives[0].plan.steps[1].name, "scale-deployment");
+        assert_eq!(alternatives[1].plan.steps[1].name, "rollback-deployment");
+        assert_eq!(alternatives[1].success_probability, 0.4);
+    }
+
+    #[test]
+    fn replan_resumes_from_completed_steps_without_failed_action() {
+        let beliefs = rules::derive_beliefs(&[alert("inc-3", "pod crashlooping", &[])]);
+        let initial = incident_state(&beliefs, "inc-3");
+        let runbook = runbooks::crashloop_runbook();
+        let scale = ActionSchema::new("scale-deployment", rig_effects::Effect::Mutate)
+            .requires("has_pod_logs")
+            .adds("remediation_applied");
+        let mut actions = runbook.clone();
+        actions.push(scale);
+
+        let state = state_after_failure(&initial, &runbook[..1], &runbook[1]);
+        assert!(state.has("has_pod_logs"));
+        assert!(state.has("failed:rollback-deployment"));
+
+        let plan = replan(
+            &state,
+            &["recovery_verified".to_string()],
+            &actions,
+            &["rollback-deployment".to_string()],
+            &OutcomeModel::new(),
+        )
+        .expect("plan");
+        assert_eq!(step_names(&plan), vec!["scale-deployment", "verify-recovery"]);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::facts::{AlertFact, Fact};
+use crate::datalog::{atom, rule, val, var, Database, Literal, Program, Rule, Term, Tuple};
+use crate::facts::{AlertFact, DeployFact, Fact, PodFact, Severity};
+use fact_registry::normalize_label_key;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IncidentPattern {
@@ -7,27 +11,569 @@ pub enum IncidentPattern {
     Generic,
 }
 
+/// The pattern an alert signals on its own; other facts only contribute
+/// alongside an alert for the same service.
 pub fn detect_pattern(fact: &Fact) -> IncidentPattern {
-    match fact {
-        Fact::Alert(AlertFact { title, tags, .. }) => {
-            let title_lower = title.to_lowercase();
-            let tags_lower = tags
-                .iter()
-                .map(|t| t.to_lowercase())
-                .collect::<Vec<_>>();
-
-            if title_lower.contains("crashloop")
-                || tags_lower.iter().any(|t| t.contains("crashloop"))
-            {
-                IncidentPattern::CrashLoop
-            } else if title_lower.contains("oom")
-                || title_lower.contains("out of memory")
-                || tags_lower.iter().any(|t| t.contains("oom"))
-            {
-                IncidentPattern::OomKill
-            } else {
-                IncidentPattern::Generic
+    let Fact::Alert(alert) = fact else {
+        return IncidentPattern::Generic;
+    };
+    derive_beliefs(std::slice::from_ref(fact)).pattern(&alert.id)
+}
+
+/// Beliefs derived from the current fact set by the incident Datalog program.
+///
+/// Base relations (extracted from facts):
+/// - `alert(id, severity)`
+/// - `signal(id, kind)` — lexical signals in title/tags: `crashloop`, `oom`, `deploy`
+/// - `label(id, key, value)` — from labels and `key:value` tags, with keys
+///   normalized (`ns` → `namespace`, `app` → `service`, ...); `status` aside
+/// - `alert_status(id, status)` — `resolved` or `firing`, from the latest
+///   alert for the id only, so a re-trigger replaces an earlier resolution
+/// - `depends_on(service, dependency)` — from `depends_on:` labels
+/// - `pod(service, pod)`, `pod_reason(service, reason)`, `pod_unready(service, pod)`
+/// - `deploy_degraded(service, deployment)` — fewer replicas available than desired
+/// - `metric_breached(service, metric)` — a sample above its threshold
+/// - `error_logged(service)` — an `error` or `fatal` log line
+/// - `change(change_id, service, kind)`
+///
+/// Derived relations:
+/// - `service(id, service)`, `namespace(id, namespace)`, `deployment(id, deployment)`
+/// - `crashloop_detected(id)`, `oomkill_detected(id)`, `recent_deploy(id)` —
+///   from the alert's own signals or from its service's pods and changes
+/// - `pods_unready(id)`, `deployment_degraded(id)`, `threshold_breached(id)`,
+///   `errors_logged(id)` — infrastructure state of the incident's service
+/// - `alert_resolved(id)` — the latest alert for the id reports it resolved
+/// - `deploy_correlated(id)` — crashloop on a service with a recent deploy
+/// - `failing_service(service)`
+/// - `reachable(service, dependency)` — transitive `depends_on`
+/// - `impacted_service(service, failing_dependency)`, `has_failing_dependency(service)`
+/// - `root_cause_candidate(service)` — failing with no failing dependency
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Beliefs {
+    db: Database,
+}
+
+impl Beliefs {
+    pub fn holds(&self, relation: &str, args: &[&str]) -> bool {
+        let tuple = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        self.db.contains(relation, &tuple)
+    }
+
+    pub fn query(&self, relation: &str) -> Vec<Tuple> {
+        self.db.tuples(relation).cloned().collect()
+    }
+
+    /// Relation names that currently hold, usable as planner propositions.
+    pub fn propositions(&self) -> BTreeSet<String> {
+        self.db.relation_names().map(ToString::to_string).collect()
+    }
+
+    /// Relation names holding a tuple keyed by `incident_id` (first column).
+    pub fn propositions_for(&self, incident_id: &str) -> BTreeSet<String> {
+        self.db
+            .relation_names()
+            .filter(|name| {
+                self.db
+                    .tuples(name)
+                    .any(|t| t.first().is_some_and(|id| id == incident_id))
+            })
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// The incident's labels by normalized key; where a key has several
+    /// values, the greatest wins.
+    pub fn labels(&self, incident_id: &str) -> BTreeMap<String, String> {
+        self.db
+            .tuples("label")
+            .filter(|t| t.first().is_some_and(|id| id == incident_id))
+            .filter_map(|t| Some((t.get(1)?.clone(), t.get(2)?.clone())))
+            .collect()
+    }
+
+    pub fn pattern(&self, incident_id: &str) -> IncidentPattern {
+        if self.holds("crashloop_detected", &[incident_id]) {
+            IncidentPattern::CrashLoop
+        } else if self.holds("oomkill_detected", &[incident_id]) {
+            IncidentPattern::OomKill
+        } else {
+            IncidentPattern::Generic
+        }
+    }
+}
+
+pub fn derive_beliefs(facts: &[Fact]) -> Beliefs {
+    let mut db = Database::default();
+    let mut latest = BTreeMap::new();
+    for fact in facts {
+        if let Fact::Alert(alert) = fact {
+            latest.insert(alert.id.as_str(), alert);
+        }
+    }
+    for (id, alert) in latest {
+        let status = if is_resolution(alert) {
+            "resolved"
+        } else {
+            "firing"
+        };
+        db.insert("alert_status", vec![id.to_string(), status.into()]);
+    }
+    for fact in facts {
+        match fact {
+            Fact::Alert(alert) => load_alert(&mut db, alert),
+            Fact::Pod(pod) => load_pod(&mut db, pod),
+            Fact::Deploy(deploy) => load_deploy(&mut db, deploy),
+            Fact::Metric(metric) => {
+                if metric.breached() {
+                    db.insert(
+                        "metric_breached",
+                        vec![metric.service.clone(), metric.name.clone()],
+                    );
+                }
+            }
+            Fact::Log(log) => {
+                if matches!(log.level.as_str(), "error" | "fatal") {
+                    db.insert("error_logged", vec![log.service.clone()]);
+                }
+            }
+            Fact::Change(change) => {
+                db.insert(
+                    "change",
+                    vec![
+                        change.id.clone(),
+                        change.service.clone(),
+                        change.kind.to_lowercase(),
+                    ],
+                );
             }
         }
     }
+    incident_program().run(&mut db);
+    Beliefs { db }
+}
+
+fn load_alert(db: &mut Database, alert: &AlertFact) {
+    let id = alert.id.clone();
+    db.insert(
+        "alert",
+        vec![id.clone(), severity_name(&alert.severity).into()],
+    );
+
+    let title_lower = alert.title.to_lowercase();
+    let tags_lower = alert
+        .tags
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect::<Vec<_>>();
+    let label_values_lower = alert
+        .labels
+        .values()
+        .map(|v| v.to_lowercase())
+        .collect::<Vec<_>>();
+    let mentions = |needle: &str| {
+        title_lower.contains(needle)
+            || tags_lower
+                .iter()
+                .chain(&label_values_lower)
+                .any(|t| t.contains(needle))
+    };
+
+    if mentions("crashloop") {
+        db.insert("signal", vec![id.clone(), "crashloop".into()]);
+    }
+    if mentions("oom") || title_lower.contains("out of memory") {
+        db.insert("signal", vec![id.clone(), "oom".into()]);
+    }
+    if mentions("deploy") || mentions("rollout") {
+        db.insert("signal", vec![id.clone(), "deploy".into()]);
+    }
+
+    let tag_labels = tags_lower
+        .iter()
+        .filter_map(|tag| tag.split_once(':'))
+        .map(|(key, value)| (normalize_label_key(key), value.to_string()));
+    let labels = alert
+        .labels
+        .iter()
+        .map(|(key, value)| (normalize_label_key(key), value.clone()));
+    for (key, value) in tag_labels.chain(labels) {
+        // Status is the latest alert's alone; see `alert_status`.
+        if key != "status" {
+            db.insert("label", vec![id.clone(), key, value]);
+        }
+    }
+}
+
+/// Whether `alert` reports its incident resolved, by a `status:resolved`
+/// tag or a `status` label.
+fn is_resolution(alert: &AlertFact) -> bool {
+    let resolved = |key: &str, value: &str| {
+        normalize_label_key(key) == "status" && value.eq_ignore_ascii_case("resolved")
+    };
+    alert
+        .tags
+        .iter()
+        .filter_map(|tag| tag.split_once(':'))
+        .any(|(key, value)| resolved(key, value))
+        || alert.labels.iter().any(|(key, value)| resolved(key, value))
+}
+
+fn load_pod(db: &mut Database, pod: &PodFact) {
+    let service = pod.service.clone();
+    db.insert("pod", vec![service.clone(), pod.name.clone()]);
+    if let Some(reason) = &pod.reason {
+        db.insert("pod_reason", vec![service.clone(), reason.to_lowercase()]);
+    }
+    if !pod.ready {
+        db.insert("pod_unready", vec![service, pod.name.clone()]);
+    }
+}
+
+fn load_deploy(db: &mut Database, deploy: &DeployFact) {
+    if deploy.available_replicas < deploy.replicas {
+        db.insert(
+            "deploy_degraded",
+            vec![deploy.service.clone(), deploy.name.clone()],
+        );
+    }
+}
+
+/// `head(id)` for every incident whose service has a `relation(service, ..)`
+/// tuple with `args` after the service.
+fn service_state(head: &str, relation: &str, args: Vec<Term>) -> Rule {
+    let mut terms = vec![var("svc")];
+    terms.extend(args);
+    rule(
+        atom(head, vec![var("id")]),
+        vec![
+            Literal::Pos(atom("service", vec![var("id"), var("svc")])),
+            Literal::Pos(atom(relation, terms)),
+        ],
+    )
+}
+
+fn incident_program() -> Program {
+    use Literal::{Neg, Neq, Pos};
+
+    Program::new()
+        .stratum(vec![
+            rule(
+                atom("service", vec![var("id"), var("svc")]),
+                vec![Pos(atom(
+                    "label",
+                    vec![var("id"), val("service"), var("svc")],
+                ))],
+            ),
+            rule(
+                atom("namespace", vec![var("id"), var("ns")]),
+                vec![Pos(atom(
+                    "label",
+                    vec![var("id"), val("namespace"), var("ns")],
+                ))],
+            ),
+            rule(
+                atom("deployment", vec![var("id"), var("deploy")]),
+                vec![Pos(atom(
+                    "label",
+                    vec![var("id"), val("deployment"), var("deploy")],
+                ))],
+            ),
+            rule(
+                atom("depends_on", vec![var("svc"), var("dep")]),
+                vec![
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom(
+                        "label",
+                        vec![var("id"), val("depends_on"), var("dep")],
+                    )),
+                ],
+            ),
+            rule(
+                atom("crashloop_detected", vec![var("id")]),
+                vec![Pos(atom("signal", vec![var("id"), val("crashloop")]))],
+            ),
+            rule(
+                atom("oomkill_detected", vec![var("id")]),
+                vec![Pos(atom("signal", vec![var("id"), val("oom")]))],
+            ),
+            service_state(
+                "crashloop_detected",
+                "pod_reason",
+                vec![val("crashloopbackoff")],
+            ),
+            service_state("oomkill_detected", "pod_reason", vec![val("oomkilled")]),
+            rule(
+                atom("alert_resolved", vec![var("id")]),
+                vec![Pos(atom("alert_status", vec![var("id"), val("resolved")]))],
+            ),
+            rule(
+                atom("recent_deploy", vec![var("id")]),
+                vec![Pos(atom("signal", vec![var("id"), val("deploy")]))],
+            ),
+            rule(
+                atom("recent_deploy", vec![var("id")]),
+                vec![
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom(
+                        "change",
+                        vec![var("_change"), var("svc"), val("deploy")],
+                    )),
+                ],
+            ),
+            service_state("pods_unready", "pod_unready", vec![var("_pod")]),
+            service_state(
+                "deployment_degraded",
+                "deploy_degraded",
+                vec![var("_deploy")],
+            ),
+            service_state(
+                "threshold_breached",
+                "metric_breached",
+                vec![var("_metric")],
+            ),
+            service_state("errors_logged", "error_logged", Vec::new()),
+            rule(
+                atom("deploy_correlated", vec![var("id")]),
+                vec![
+                    Pos(atom("crashloop_detected", vec![var("id")])),
+                    Pos(atom("recent_deploy", vec![var("id")])),
+                ],
+            ),
+            rule(
+                atom("deploy_correlated", vec![var("id")]),
+                vec![
+                    Pos(atom("crashloop_detected", vec![var("id")])),
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom("service", vec![var("other"), var("svc")])),
+                    Pos(atom("recent_deploy", vec![var("other")])),
+                ],
+            ),
+            rule(
+                atom("failing_service", vec![var("svc")]),
+                vec![
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom("crashloop_detected", vec![var("id")])),
+                ],
+            ),
+            rule(
+                atom("failing_service", vec![var("svc")]),
+                vec![
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom("oomkill_detected", vec![var("id")])),
+                ],
+            ),
+            rule(
+                atom("failing_service", vec![var("svc")]),
+                vec![
+                    Pos(atom("service", vec![var("id"), var("svc")])),
+                    Pos(atom("alert", vec![var("id"), val("critical")])),
+                ],
+            ),
+            rule(
+                atom("reachable", vec![var("a"), var("b")]),
+                vec![Pos(atom("depends_on", vec![var("a"), var("b")]))],
+            ),
+            rule(
+                atom("reachable", vec![var("a"), var("c")]),
+                vec![
+                    Pos(atom("reachable", vec![var("a"), var("b")])),
+                    Pos(atom("depends_on", vec![var("b"), var("c")])),
+                ],
+            ),
+            rule(
+                atom("impacted_service", vec![var("svc"), var("dep")]),
+                vec![
+                    Pos(atom("reachable", vec![var("svc"), var("dep")])),
+                    Pos(atom("failing_service", vec![var("dep")])),
+                    Neq(var("svc"), var("dep")),
+                ],
+            ),
+            rule(
+                atom("has_failing_dependency", vec![var("svc")]),
+                vec![Pos(atom("impacted_service", vec![var("svc"), var("_dep")]))],
+            ),
+        ])
+        .stratum(vec![rule(
+            atom("root_cause_candidate", vec![var("svc")]),
+            vec![
+                Pos(atom("failing_service", vec![var("svc")])),
+                Neg(atom("has_failing_dependency", vec![var("svc")])),
+            ],
+        )])
+}
+
+fn severity_name(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Low => "low",
+        Severity::Medium => "medium",
+        Severity::High => "high",
+        Severity::Critical => "critical",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facts::{AlertSource, ChangeFact, MetricFact};
+
+    fn alert(id: &str, title: &str, tags: &[&str]) -> Fact {
+        Fact::Alert(AlertFact {
+            id: id.into(),
+            source: AlertSource::Generic,
+            severity: Severity::High,
+            title: title.into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            received_at: "1".into(),
+            occurred_at: None,
+            labels: Default::default(),
+        })
+    }
+
+    #[test]
+    fn detect_pattern_matches_title_and_tags() {
+        assert_eq!(
+            detect_pattern(&alert("a", "Pod CrashLoopBackOff", &[])),
+            IncidentPattern::CrashLoop
+        );
+        assert_eq!(
+            detect_pattern(&alert("b", "memory", &["oom"])),
+            IncidentPattern::OomKill
+        );
+        assert_eq!(
+            detect_pattern(&alert("c", "cpu high", &[])),
+            IncidentPattern::Generic
+        );
+    }
+
+    #[test]
+    fn correlates_crashloop_with_recent_deploy_on_same_service() {
+        let beliefs = derive_beliefs(&[
+            alert("inc-1", "checkout crashlooping", &["service:checkout"]),
+            alert("chg-1", "deploy finished", &["service:checkout"]),
+        ]);
+
+        assert!(beliefs.holds("deploy_correlated", &["inc-1"]));
+        assert!(beliefs.propositions().contains("crashloop_detected"));
+        assert!(beliefs
+            .propositions_for("inc-1")
+            .contains("deploy_correlated"));
+        assert!(!beliefs
+            .propositions_for("chg-1")
+            .contains("crashloop_detected"));
+    }
+
+    #[test]
+    fn derives_transitive_dependency_impact() {
+        let beliefs = derive_beliefs(&[
+            alert(
+                "a",
+                "frontend errors",
+                &["service:frontend", "depends_on:checkout"],
+            ),
+            alert(
+                "b",
+                "checkout errors",
+                &["service:checkout", "depends_on:payments"],
+            ),
+            alert("c", "payments crashloop", &["service:payments"]),
+        ]);
+
+        assert!(beliefs.holds("reachable", &["frontend", "payments"]));
+        assert!(beliefs.holds("impacted_service", &["frontend", "payments"]));
+        assert!(beliefs.holds("root_cause_candidate", &["payments"]));
+    }
+
+    #[test]
+    fn infrastructure_facts_inform_incidents_on_their_service() {
+        let pod = |service: &str, reason: &str| {
+            Fact::Pod(PodFact {
+                id: format!("pod/shop/{service}-1"),
+                name: format!("{service}-1"),
+                namespace: "shop".into(),
+                service: service.into(),
+                phase: "running".into(),
+                restarts: 7,
+                ready: false,
+                reason: Some(reason.into()),
+                observed_at: "1".into(),
+            })
+        };
+        let beliefs = derive_beliefs(&[
+            alert("inc-1", "checkout errors", &["service:checkout"]),
+            alert("inc-2", "search latency", &["service:search"]),
+            pod("checkout", "CrashLoopBackOff"),
+            pod("payments", "OOMKilled"),
+            Fact::Change(ChangeFact {
+                id: "chg-1".into(),
+                service: "checkout".into(),
+                kind: "deploy".into(),
+                description: "v42".into(),
+                author: None,
+                occurred_at: "1".into(),
+            }),
+            Fact::Metric(MetricFact {
+                id: "metric/search/p99_ms".into(),
+                service: "search".into(),
+                name: "p99_ms".into(),
+                value: 900.0,
+                unit: Some("ms".into()),
+                threshold: Some(500.0),
+                observed_at: "1".into(),
+            }),
+        ]);
+
+        assert_eq!(beliefs.pattern("inc-1"), IncidentPattern::CrashLoop);
+        assert!(beliefs.holds("deploy_correlated", &["inc-1"]));
+        assert!(beliefs.holds("pods_unready", &["inc-1"]));
+        assert!(beliefs.holds("threshold_breached", &["inc-2"]));
+        assert_eq!(beliefs.pattern("inc-2"), IncidentPattern::Generic);
+        assert!(!beliefs.propositions_for("inc-2").contains("pods_unready"));
+    }
+
+    #[test]
+    fn labels_bind_runbook_parameters() {
+        let Fact::Alert(mut labelled) = alert("inc-1", "checkout crashlooping", &["ns:shop"])
+        else {
+            unreachable!()
+        };
+        labelled.labels = [("app", "checkout"), ("Deployment-Name", "checkout-api")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let beliefs = derive_beliefs(&[Fact::Alert(labelled)]);
+
+        assert!(beliefs.holds("service", &["inc-1", "checkout"]));
+        assert!(beliefs.holds("namespace", &["inc-1", "shop"]));
+        assert!(beliefs.holds("deployment", &["inc-1", "checkout-api"]));
+
+        let labels = beliefs.labels("inc-1");
+        let bound: Vec<_> = crate::runbooks::crashloop_runbook()
+            .iter()
+            .map(|step| crate::runbooks::bind_params(step, &labels))
+            .collect();
+        assert_eq!(bound[0].params["service"], "checkout");
+        assert_eq!(bound[1].params["namespace"], "shop");
+        assert_eq!(bound[1].params["deployment"], "checkout-api");
+
+        let bare = crate::runbooks::bind_params(
+            &crate::runbooks::crashloop_runbook()[1],
+            &BTreeMap::new(),
+        );
+        assert!(bare.params.is_empty());
+    }
+
+    #[test]
+    fn the_latest_alert_decides_whether_an_incident_is_resolved() {
+        let triggered = alert("inc-flap", "checkout crashlooping", &["service:checkout"]);
+        let recovered = alert("inc-flap", "checkout crashlooping", &["status:resolved"]);
+
+        let beliefs = derive_beliefs(&[triggered.clone(), recovered.clone()]);
+        assert!(beliefs.holds("alert_resolved", &["inc-flap"]));
+
+        let beliefs = derive_beliefs(&[triggered.clone(), recovered, triggered]);
+        assert!(!beliefs.holds("alert_resolved", &["inc-flap"]));
+        assert!(beliefs.holds("alert_status", &["inc-flap", "firing"]));
+        assert!(!beliefs.labels("inc-flap").contains_key("status"));
+    }
 }
@@ -1,36 +1,152 @@
 use rig_effects::Effect;
-use serde::{Deserialize, Serialize};
+use rig_planner::{HtnDomain, Method, Plan, Task};
+use std::collections::BTreeMap;
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct ActionSchema {
-    pub name: String,
-    pub effect: Effect,
-}
+pub use rig_planner::ActionSchema;
 
 pub type Runbook = Vec<ActionSchema>;
 
+/// Root compound task of [`crashloop_task_network`].
+pub const MITIGATE_CRASHLOOP: &str = "mitigate-crashloop";
+
 pub fn crashloop_runbook() -> Runbook {
-    vec![
-        ActionSchema {
-            name: "inspect-pod-logs".into(),
-            effect: Effect::Observe,
-        },
-        ActionSchema {
-            name: "rollback-deployment".into(),
-            effect: Effect::Mutate,
-        },
-    ]
+    vec![inspect_pod_logs(), rollback_deployment(), verify_recovery()]
 }
 
 pub fn oomkill_runbook() -> Runbook {
     vec![
-        ActionSchema {
-            name: "inspect-memory-metrics".into(),
-            effect: Effect::Observe,
-        },
-        ActionSchema {
-            name: "tune-memory-limits".into(),
-            effect: Effect::Mutate,
-        },
+        on_service(ActionSchema::new("inspect-memory-metrics", Effect::Observe))
+            .requires("oomkill_detected")
+            .adds("has_memory_metrics"),
+        on_deployment(ActionSchema::new("tune-memory-limits", Effect::Mutate))
+            .requires("has_memory_metrics")
+            .adds("remediation_applied")
+            .deletes("oomkill_detected"),
+        verify_recovery(),
+    ]
+}
+
+/// Undo actions for the runbooks' Mutate steps, keyed by step name, run
+/// when a plan is aborted after the step ran.
+pub fn compensations() -> BTreeMap<String, ActionSchema> {
+    [
+        ("rollback-deployment", "roll-forward-deployment"),
+        ("scale-deployment", "restore-replicas"),
+        ("tune-memory-limits", "restore-memory-limits"),
     ]
+    .into_iter()
+    .map(|(step, undo)| {
+        (
+            step.to_string(),
+            on_deployment(ActionSchema::new(undo, Effect::Mutate)),
+        )
+    })
+    .collect()
+}
+
+/// Fill the `{label}` placeholders in `step`'s params from an incident's
+/// labels. A param naming a label the incident lacks is left out, so the
+/// tool falls back to its own default rather than acting on a literal
+/// placeholder.
+pub fn bind_params(step: &ActionSchema, labels: &BTreeMap<String, String>) -> ActionSchema {
+    let mut bound = step.clone();
+    bound.params = step
+        .params
+        .iter()
+        .filter_map(|(name, template)| Some((name.clone(), fill(template, labels)?)))
+        .collect();
+    bound
+}
+
+/// `plan` with every step's params bound by [`bind_params`].
+pub fn bind_plan(mut plan: Plan, labels: &BTreeMap<String, String>) -> Plan {
+    plan.steps = plan
+        .steps
+        .iter()
+        .map(|step| bind_params(step, labels))
+        .collect();
+    plan
+}
+
+fn fill(template: &str, labels: &BTreeMap<String, String>) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = open + rest[open..].find('}')?;
+        out.push_str(&rest[..open]);
+        out.push_str(labels.get(&rest[open + 1..close])?);
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Crashloop mitigation as a task hierarchy:
+/// mitigate → diagnose (logs, events) → remediate (rollback, else scale) → verify.
+pub fn crashloop_task_network() -> HtnDomain {
+    HtnDomain::new()
+        .method(
+            MITIGATE_CRASHLOOP,
+            Method::new("diagnose-remediate-verify")
+                .requires("crashloop_detected")
+                .then(Task::compound("diagnose"))
+                .then(Task::compound("remediate"))
+                .then(verify_recovery()),
+        )
+        .method(
+            "diagnose",
+            Method::new("logs-and-events")
+                .then(inspect_pod_logs())
+                .then(
+                    on_service(ActionSchema::new("inspect-pod-events", Effect::Observe))
+                        .requires("crashloop_detected")
+                        .adds("has_pod_events"),
+                ),
+        )
+        .method(
+            "remediate",
+            Method::new("rollback")
+                .requires("deploy_correlated")
+                .then(rollback_deployment()),
+        )
+        .method(
+            "remediate",
+            Method::new("scale").then(
+                on_deployment(ActionSchema::new("scale-deployment", Effect::Mutate))
+                    .requires("has_pod_events")
+                    .adds("remediation_applied")
+                    .deletes("crashloop_detected"),
+            ),
+        )
+}
+
+/// Params locating the incident's service.
+fn on_service(step: ActionSchema) -> ActionSchema {
+    step.param("namespace", "{namespace}")
+        .param("service", "{service}")
+}
+
+/// Params locating the incident's deployment.
+fn on_deployment(step: ActionSchema) -> ActionSchema {
+    step.param("namespace", "{namespace}")
+        .param("deployment", "{deployment}")
+}
+
+fn inspect_pod_logs() -> ActionSchema {
+    on_service(ActionSchema::new("inspect-pod-logs", Effect::Observe))
+        .requires("crashloop_detected")
+        .adds("has_pod_logs")
+}
+
+fn rollback_deployment() -> ActionSchema {
+    on_deployment(ActionSchema::new("rollback-deployment", Effect::Mutate))
+        .requires("has_pod_logs")
+        .adds("remediation_applied")
+        .deletes("crashloop_detected")
+}
+
+fn verify_recovery() -> ActionSchema {
+    on_service(ActionSchema::new("verify-recovery", Effect::Observe))
+        .requires("remediation_applied")
+        .adds("recovery_verified")
 }
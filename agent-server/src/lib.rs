@@ -1 +1,3 @@
+pub mod mapping;
+pub mod schemas;
 pub mod webhook;
@@ -1,27 +1,17 @@
-use agent_core::{agent, event_log, runbooks, streams};
+use agent_core::facts::AlertSource;
+use agent_core::{agent, event_log, streams};
+use agent_server::mapping;
+use agent_server::webhook::AdapterRegistry;
 
 #[tokio::main]
 async fn main() {
-    let log = event_log::EventLog::open("incidents.db").expect("open event log");
+    let log = event_log::EventLog::open("incidents.db")
+        .expect("open event log")
+        .with_registry(agent_server::schemas::registry().clone());
     let (webhook_tx, webhook_stream) = streams::webhook_channel(256);
     let (escalation_tx, escalation_rx) = std::sync::mpsc::channel();
 
-    let config = agent::AgentConfig {
-        max_replan_attempts: 3,
-        runbooks: vec![
-            ("crashloop_runbook", runbooks::crashloop_runbook()),
-            ("oomkill_runbook", runbooks::oomkill_runbook()),
-        ],
-        all_actions: vec![
-            runbooks::crashloop_runbook(),
-            runbooks::oomkill_runbook(),
-        ]
-        .into_iter()
-        .flatten()
-        .collect(),
-        goal_props: vec!["recovery_verified".into()],
-        llm: build_llm_config_from_env(),
-    };
+    let config = agent::AgentConfig::standard(build_llm_config_from_env());
 
     let log_for_agent = log.clone();
     std::thread::spawn(move || {
@@ -36,7 +26,19 @@ async fn main() {
         }
     });
 
-    let app = agent_server::webhook::webhook_router(webhook_tx);
+    let (subscription_tx, subscription_rx) = std::sync::mpsc::channel();
+    let adapters = load_adapters().subscriptions(subscription_tx);
+    std::thread::spawn(move || {
+        while let Ok(req) = subscription_rx.recv() {
+            println!(
+                "webhook {}: confirm the subscription at {}",
+                req.source, req.url
+            );
+        }
+    });
+
+    let app = agent_server::webhook::adapter_router(adapters, webhook_tx)
+        .merge(agent_server::schemas::schema_router());
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080")
         .await
         .expect("bind :8080");
@@ -45,6 +47,30 @@ async fn main() {
     axum::serve(listener, app).await.expect("serve");
 }
 
+/// The built-in adapters plus the field mappings in `ADAPTER_DIR`, which
+/// defaults to this crate's `adapters` directory. Only a directory set
+/// explicitly has to exist.
+fn load_adapters() -> AdapterRegistry {
+    let default_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/adapters");
+    let (dir, explicit) = match std::env::var("ADAPTER_DIR") {
+        Ok(dir) => (dir, true),
+        Err(_) => (default_dir.to_string(), false),
+    };
+    let path = std::path::Path::new(&dir);
+    let mappings = if explicit || path.is_dir() {
+        mapping::load_mappings(path).expect("load adapters")
+    } else {
+        Vec::new()
+    };
+    mappings
+        .into_iter()
+        .fold(AdapterRegistry::builtin(), |adapters, mapping| {
+            println!("webhook adapter {} from {dir}", mapping.name);
+            let source = AlertSource::Mapped(mapping.name.clone());
+            adapters.register(mapping.name.clone(), source, mapping)
+        })
+}
+
 fn build_llm_config_from_env() -> Option<agent_core::llm::LlmConfig> {
     let api_key_env = std::env::var("LLM_API_KEY_ENV").unwrap_or_else(|_| "OPENAI_API_KEY".into());
     if std::env::var(&api_key_env).is_err() {
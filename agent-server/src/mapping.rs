@@ -0,0 +1,510 @@
+//! Alert sources defined by field mappings instead of code: each mapping
+//! names JSONPath-like selectors for an alert's fields, loaded from TOML
+//! files at startup and served as a [`FactAdapter`].
+
+use crate::webhook::{current_timestamp, FactAdapter};
+use fact_registry::{CanonicalAlertV1, ValidationReport, Violation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// How to read one source's payloads. Selectors start at `$`, the alert
+/// being read: the payload itself, or each item [`FieldMapping::alerts`]
+/// expands it into.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldMapping {
+    /// The source name, served at `/webhook/{name}`.
+    pub name: String,
+    /// Where the alerts are when a payload carries several or nests one: an
+    /// array is read as one alert per item, and a string is decoded as JSON
+    /// first, as SNS does with its `Message`.
+    #[serde(default)]
+    pub alerts: Option<Selector>,
+    pub id: Selector,
+    pub title: Selector,
+    #[serde(default)]
+    pub severity: Option<Selector>,
+    /// Source severities, matched without regard to case, to canonical
+    /// ones. Unmapped values are passed through for the registry to check.
+    #[serde(default)]
+    pub severity_map: BTreeMap<String, String>,
+    #[serde(default = "default_severity")]
+    pub default_severity: String,
+    /// An array of tags, a comma-separated string, or an object read as
+    /// `key:value` tags.
+    #[serde(default)]
+    pub tags: Option<Selector>,
+    /// An object of labels.
+    #[serde(default)]
+    pub labels: Option<Selector>,
+    /// Single labels by key, taking precedence over [`FieldMapping::labels`].
+    #[serde(default)]
+    pub label: BTreeMap<String, Selector>,
+    #[serde(default)]
+    pub occurred_at: Option<Selector>,
+    #[serde(default)]
+    pub resolved: Option<Resolution>,
+    #[serde(default)]
+    pub confirm: Option<Confirmation>,
+}
+
+/// The alert is resolved when `when` reads as one of `equals`, compared
+/// without regard to case.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Resolution {
+    pub when: Selector,
+    pub equals: Vec<String>,
+}
+
+/// A delivery that only asks for the subscription to be confirmed, such as
+/// SNS's `SubscriptionConfirmation`, when `when` reads as one of `equals`.
+/// It carries no alerts, only the `url` that confirms it.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Confirmation {
+    pub when: Selector,
+    pub equals: Vec<String>,
+    pub url: Selector,
+}
+
+fn default_severity() -> String {
+    "high".into()
+}
+
+impl FieldMapping {
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Each alert in `payload`, with the JSON Pointer it was found at.
+    fn expand(&self, payload: &Value) -> Result<Vec<(String, Value)>, ValidationReport> {
+        let Some(selector) = &self.alerts else {
+            return Ok(vec![(String::new(), payload.clone())]);
+        };
+        let missing = || Violation::new(selector.pointer(), "required", "payload has no alerts");
+        let found = match selector.select(payload).ok_or_else(missing)? {
+            Value::String(text) => serde_json::from_str(text).map_err(|e| {
+                Violation::new(selector.pointer(), "invalid", format!("not JSON: {e}"))
+            })?,
+            other => other.clone(),
+        };
+        let at = selector.pointer();
+        match found {
+            Value::Array(items) if items.is_empty() => Err(missing().into()),
+            Value::Array(items) => Ok(items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| (format!("{at}/{index}"), item))
+                .collect()),
+            other => Ok(vec![(at, other)]),
+        }
+    }
+
+    fn alert(&self, item: &Value) -> Result<CanonicalAlertV1, ValidationReport> {
+        let mut report = ValidationReport::new();
+        let mut required = |selector: &Selector| {
+            selector.text(item).unwrap_or_else(|| {
+                report.errors.push(Violation::new(
+                    selector.pointer(),
+                    "required",
+                    "selector matched nothing",
+                ));
+                String::new()
+            })
+        };
+        let id = required(&self.id);
+        let title = required(&self.title);
+        if !report.is_valid() {
+            return Err(report);
+        }
+
+        let severity = match self.severity.as_ref().and_then(|s| s.text(item)) {
+            Some(raw) => self
+                .severity_map
+                .iter()
+                .find(|(from, _)| from.eq_ignore_ascii_case(&raw))
+                .map_or(raw, |(_, to)| to.clone()),
+            None => self.default_severity.clone(),
+        };
+
+        let mut tags = self
+            .tags
+            .as_ref()
+            .and_then(|s| s.select(item))
+            .map(tags_of)
+            .unwrap_or_default();
+        let resolved = self.resolved.as_ref();
+        if resolved.is_some_and(|r| reads_as(&r.when, &r.equals, item)) {
+            tags.push("status:resolved".into());
+        }
+
+        let mut labels = self
+            .labels
+            .as_ref()
+            .and_then(|s| s.select(item))
+            .and_then(Value::as_object)
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(k, v)| Some((k.clone(), scalar(v)?)))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default();
+        for (key, selector) in &self.label {
+            if let Some(value) = selector.text(item) {
+                labels.insert(key.clone(), value);
+            }
+        }
+
+        Ok(CanonicalAlertV1 {
+            schema: "alert.v1".into(),
+            id,
+            title,
+            severity,
+            tags,
+            labels,
+            source: self.name.clone(),
+            occurred_at: self
+                .occurred_at
+                .as_ref()
+                .and_then(|s| s.text(item))
+                .unwrap_or_else(current_timestamp),
+        })
+    }
+}
+
+impl FactAdapter for FieldMapping {
+    fn subscription(&self, payload: &Value) -> Option<String> {
+        let confirm = self.confirm.as_ref()?;
+        reads_as(&confirm.when, &confirm.equals, payload)
+            .then(|| confirm.url.text(payload).unwrap_or_default())
+    }
+
+    fn parse(&self, payload: &Value) -> Result<CanonicalAlertV1, ValidationReport> {
+        let (at, first) = self.expand(payload)?.swap_remove(0);
+        self.alert(&first).map_err(|report| within(&at, report))
+    }
+
+    fn parse_all(&self, payload: &Value) -> Result<Vec<CanonicalAlertV1>, ValidationReport> {
+        let mut alerts = Vec::new();
+        let mut report = ValidationReport::new();
+        for (at, item) in self.expand(payload)? {
+            match self.alert(&item) {
+                Ok(alert) => alerts.push(alert),
+                Err(errors) => report.merge(within(&at, errors)),
+            }
+        }
+        if report.is_valid() {
+            Ok(alerts)
+        } else {
+            Err(report)
+        }
+    }
+}
+
+/// Every `*.toml` mapping in `dir`, in file name order.
+pub fn load_mappings(dir: &std::path::Path) -> Result<Vec<FieldMapping>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| FieldMapping::from_toml(&text))
+                .map_err(|e| format!("{}: {e}", path.display()))
+        })
+        .collect()
+}
+
+/// One or more paths, tried in order until one matches a non-null value.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "OneOrMany")]
+pub struct Selector(Vec<JsonPath>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TryFrom<OneOrMany> for Selector {
+    type Error = String;
+
+    fn try_from(raw: OneOrMany) -> Result<Self, String> {
+        let paths = match raw {
+            OneOrMany::One(path) => vec![path],
+            OneOrMany::Many(paths) => paths,
+        };
+        if paths.is_empty() {
+            return Err("a selector needs at least one path".into());
+        }
+        paths
+            .iter()
+            .map(|path| path.parse())
+            .collect::<Result<_, _>>()
+            .map(Selector)
+    }
+}
+
+impl Selector {
+    pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.0
+            .iter()
+            .filter_map(|path| path.select(value))
+            .find(|found| !found.is_null())
+    }
+
+    /// The first match as text: strings as they are, numbers and booleans
+    /// written out. Blank strings count as no match.
+    pub fn text(&self, value: &Value) -> Option<String> {
+        self.0
+            .iter()
+            .filter_map(|path| path.select(value))
+            .find_map(scalar)
+    }
+
+    /// The first path as a JSON Pointer, for reporting.
+    fn pointer(&self) -> String {
+        self.0[0].pointer()
+    }
+}
+
+/// A path such as `$.alert.tags`, `$.alerts[0]` or `$.labels['app.name']`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonPath(Vec<Segment>);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl FromStr for JsonPath {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let invalid = |why: &str| format!("invalid path '{text}': {why}");
+        let mut rest = text
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?;
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end == 0 {
+                    return Err(invalid("empty key"));
+                }
+                segments.push(Segment::Key(after[..end].to_string()));
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix("['") {
+                let end = after.find("']").ok_or_else(|| invalid("unclosed ['"))?;
+                segments.push(Segment::Key(after[..end].to_string()));
+                rest = &after[end + 2..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(|| invalid("unclosed ["))?;
+                let index = after[..end]
+                    .parse()
+                    .map_err(|_| invalid("index must be a whole number"))?;
+                segments.push(Segment::Index(index));
+                rest = &after[end + 1..];
+            } else {
+                return Err(invalid("expected '.' or '['"));
+            }
+        }
+        Ok(Self(segments))
+    }
+}
+
+impl JsonPath {
+    pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.0.iter().try_fold(value, |at, segment| match segment {
+            Segment::Key(key) => at.get(key),
+            Segment::Index(index) => at.get(index),
+        })
+    }
+
+    fn pointer(&self) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+                Segment::Index(index) => format!("/{index}"),
+            })
+            .collect()
+    }
+}
+
+/// `report` with its pointers, relative to one alert, made relative to the
+/// payload the alert was found at `at` in.
+fn within(at: &str, mut report: ValidationReport) -> ValidationReport {
+    for violation in report.errors.iter_mut().chain(&mut report.warnings) {
+        violation.pointer.insert_str(0, at);
+    }
+    report
+}
+
+/// Whether `when` reads as one of `equals`, compared without regard to case.
+fn reads_as(when: &Selector, equals: &[String], item: &Value) -> bool {
+    when.text(item).is_some_and(|state| {
+        equals
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(&state))
+    })
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) if !text.trim().is_empty() => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+fn tags_of(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(scalar).collect(),
+        Value::String(text) => text
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(ToString::to_string)
+            .collect(),
+        Value::Object(obj) => obj
+            .iter()
+            .filter_map(|(k, v)| Some(format!("{k}:{}", scalar(v)?)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhook::AdapterRegistry;
+    use agent_core::facts::{AlertSource, Fact, Severity};
+
+    fn alerts(mapping: &str, fixture: &str) -> Vec<agent_core::facts::AlertFact> {
+        let mapping = FieldMapping::from_toml(mapping).expect("mapping");
+        let name = mapping.name.clone();
+        let payload: Value = serde_json::from_str(fixture).expect("fixture");
+        AdapterRegistry::new()
+            .register(name.clone(), AlertSource::Mapped(name.clone()), mapping)
+            .parse(&name, &payload)
+            .expect("registered")
+            .expect("accepted")
+            .value
+            .into_iter()
+            .map(|fact| match fact {
+                Fact::Alert(alert) => alert,
+                other => panic!("expected an alert, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_paths_and_rejects_malformed_ones() {
+        let path: JsonPath = "$.alerts[1]['app.kubernetes.io/name']".parse().unwrap();
+        let payload = serde_json::json!({"alerts": [{}, {"app.kubernetes.io/name": "checkout"}]});
+        assert_eq!(path.select(&payload), Some(&serde_json::json!("checkout")));
+        assert_eq!(path.pointer(), "/alerts/1/app.kubernetes.io~1name");
+
+        for bad in ["alerts", "$..a", "$.a[x]", "$.a['b"] {
+            assert!(bad.parse::<JsonPath>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn maps_grafana_alert_groups() {
+        let alerts = alerts(
+            include_str!("../adapters/grafana.toml"),
+            include_str!("../fixtures/grafana.json"),
+        );
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].id, "a1b2c3d4e5f60718");
+        assert_eq!(alerts[0].source, AlertSource::Mapped("grafana".into()));
+        assert_eq!(alerts[0].title, "Checkout 5xx rate above 5%");
+        assert_eq!(alerts[0].severity, Severity::Critical);
+        assert_eq!(alerts[0].labels["namespace"], "shop");
+        assert_eq!(alerts[0].labels["service"], "checkout");
+        assert!(!alerts[0].tags.iter().any(|t| t == "status:resolved"));
+        assert_eq!(alerts[1].severity, Severity::Medium);
+        assert!(alerts[1].tags.iter().any(|t| t == "status:resolved"));
+    }
+
+    #[test]
+    fn points_at_the_alert_a_violation_is_in() {
+        let mapping =
+            FieldMapping::from_toml(include_str!("../adapters/grafana.toml")).expect("mapping");
+        let mut payload: Value =
+            serde_json::from_str(include_str!("../fixtures/grafana.json")).expect("fixture");
+        payload["alerts"][1]
+            .as_object_mut()
+            .expect("alert")
+            .remove("fingerprint");
+
+        let errors = mapping.parse_all(&payload).expect_err("no id").errors;
+        let pointers: Vec<&str> = errors.iter().map(|v| v.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/alerts/1/fingerprint"]);
+    }
+
+    #[test]
+    fn maps_opsgenie_priorities_and_closes() {
+        let mapping = include_str!("../adapters/opsgenie.toml");
+        let created = alerts(mapping, include_str!("../fixtures/opsgenie.json"));
+
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].id, "70413a06-38d6-4c85-92b8-5ebc900d42e2");
+        assert_eq!(created[0].severity, Severity::Critical);
+        assert_eq!(created[0].labels["service"], "payments");
+        assert!(created[0].tags.iter().any(|t| t == "database"));
+        assert_eq!(
+            created[0].occurred_at.map(|t| t.timestamp()),
+            Some(1_736_510_400)
+        );
+
+        let closed = include_str!("../fixtures/opsgenie.json").replace("\"Create\"", "\"Close\"");
+        assert!(alerts(mapping, &closed)[0]
+            .tags
+            .iter()
+            .any(|t| t == "status:resolved"));
+    }
+
+    #[test]
+    fn maps_cloudwatch_alarms_from_sns_messages() {
+        let alerts = alerts(
+            include_str!("../adapters/cloudwatch-sns.toml"),
+            include_str!("../fixtures/cloudwatch-sns.json"),
+        );
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, "checkout-high-cpu");
+        assert_eq!(alerts[0].title, "Checkout CPU above 90% for 5 minutes");
+        assert_eq!(alerts[0].severity, Severity::High);
+        assert_eq!(alerts[0].labels["metric"], "CPUUtilization");
+        assert_eq!(alerts[0].labels["region"], "US East (N. Virginia)");
+        assert!(!alerts[0].labels.contains_key("namespace"));
+    }
+
+    #[test]
+    fn sns_subscription_confirmations_carry_their_url() {
+        let mapping = FieldMapping::from_toml(include_str!("../adapters/cloudwatch-sns.toml"))
+            .expect("mapping");
+        let read =
+            |fixture: &str| mapping.subscription(&serde_json::from_str(fixture).expect("fixture"));
+        let url = read(include_str!("../fixtures/cloudwatch-sns-confirmation.json"))
+            .expect("confirmation");
+        assert!(url.starts_with("https://sns.us-east-1.amazonaws.com/?Action=ConfirmSubscription"));
+        assert_eq!(read(include_str!("../fixtures/cloudwatch-sns.json")), None);
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{Json, Router, extract::Path, http::StatusCode, routing::get};
+use fact_registry::{SchemaId, SchemaRegistry};
+use std::sync::OnceLock;
+
+/// The canonical schemas, shared by the webhooks and the schema endpoint.
+pub fn registry() -> &'static SchemaRegistry {
+    static REGISTRY: OnceLock<SchemaRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(fact_registry::canonical_registry)
+}
+
+pub fn schema_router() -> Router {
+    Router::new().route("/schemas/:name", get(handle_schema))
+}
+
+/// The JSON Schema document for `name`: a versioned schema such as
+/// `alert.v1`, or a bare kind such as `alert` for its latest version.
+async fn handle_schema(Path(name): Path<String>) -> Result<Json<serde_json::Value>, StatusCode> {
+    let registry = registry();
+    let id = SchemaId::parse(&name)
+        .ok()
+        .or_else(|| registry.latest(&name))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    registry
+        .document(&id)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get(uri: &str) -> (StatusCode, Option<serde_json::Value>) {
+        let response = schema_router()
+            .oneshot(Request::get(uri).body(Body::empty()).expect("request"))
+            .await
+            .expect("response");
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        (status, serde_json::from_slice(&body).ok())
+    }
+
+    #[tokio::test]
+    async fn bare_kinds_serve_their_latest_version() {
+        let (status, document) = get("/schemas/alert").await;
+        assert_eq!(status, StatusCode::OK);
+        let latest = registry()
+            .document(&SchemaId::new("alert", 3))
+            .expect("alert.v3");
+        assert_eq!(document.as_ref(), Some(latest));
+
+        let (status, _) = get("/schemas/alert.v9").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,17 +1,107 @@
-use agent_core::facts::{AlertFact, AlertSource, Fact, Severity};
-use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
-use fact_registry::{CanonicalAlertV1, validate_alert_v1};
+use agent_core::facts::{
+    AlertFact, AlertSource, ChangeFact, DeployFact, Fact, LogFact, MetricFact, PodFact, Severity,
+};
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use crate::schemas::registry;
+use fact_registry::{
+    CanonicalAlertV1, CanonicalAlertV3, CanonicalChangeV1, CanonicalDeployV1, CanonicalLogV1,
+    CanonicalMetricV1, CanonicalPodV1, SchemaId, Timestamp, Validated, ValidationReport, Violation,
+    labels_from_tags, parse_timestamp,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 pub trait FactAdapter: Send + Sync + 'static {
-    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, String>;
+    /// The payload as a canonical alert, left for the registry to validate;
+    /// errors are for payloads the adapter cannot read at all.
+    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, ValidationReport>;
+
+    /// Check a delivery came from the source before it is read, given its
+    /// headers and raw body. Sources that do not sign deliveries accept all.
+    fn verify(&self, _headers: &HeaderMap, _body: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Every alert in the payload, for sources that batch several into one
+    /// delivery; defaults to the one alert [`FactAdapter::parse`] finds.
+    fn parse_all(
+        &self,
+        payload: &serde_json::Value,
+    ) -> Result<Vec<CanonicalAlertV1>, ValidationReport> {
+        self.parse(payload).map(|alert| vec![alert])
+    }
+
+    /// The URL that confirms a subscription, for deliveries that only ask
+    /// for one and carry no alerts. Defaults to none.
+    fn subscription(&self, _payload: &serde_json::Value) -> Option<String> {
+        None
+    }
+}
+
+/// A source asking for its subscription to be confirmed by visiting `url`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscriptionRequest {
+    pub source: String,
+    pub url: String,
+}
+
+/// A payload a webhook turned away, with the status to answer with: 400
+/// when the adapter cannot read it, 401 when its signature does not check
+/// out, 422 when it fails validation.
+#[derive(Debug)]
+pub struct Rejected {
+    pub status: StatusCode,
+    pub report: ValidationReport,
+}
+
+impl Rejected {
+    fn malformed(report: ValidationReport) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            report,
+        }
+    }
+
+    fn unauthorized(report: ValidationReport) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            report,
+        }
+    }
+
+    fn invalid(report: ValidationReport) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            report,
+        }
+    }
 }
 
 pub struct GenericAdapter;
 pub struct AlertmanagerAdapter;
+/// Datadog monitor notifications, as sent by the webhooks integration's
+/// default payload template.
+pub struct DatadogAdapter;
+
+/// PagerDuty v3 webhook subscriptions' incident events. Deliveries must be
+/// signed with one of `secrets`; with none configured, signatures are not
+/// checked.
+#[derive(Clone, Debug, Default)]
+pub struct PagerDutyAdapter {
+    secrets: Vec<String>,
+}
 
 impl FactAdapter for GenericAdapter {
-    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, String> {
-        let alert = CanonicalAlertV1 {
+    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, ValidationReport> {
+        Ok(CanonicalAlertV1 {
             schema: "alert.v1".into(),
             id: payload
                 .get("id")
@@ -39,21 +129,26 @@ impl FactAdapter for GenericAdapter {
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default(),
+            labels: payload.get("labels").map(string_map).unwrap_or_default(),
             source: "generic".into(),
-            occurred_at: current_timestamp(),
-        };
-        validate_alert_v1(&alert)?;
-        Ok(alert)
+            occurred_at: source_time(payload, &["occurred_at", "created_at", "timestamp"]),
+        })
     }
 }
 
 impl FactAdapter for AlertmanagerAdapter {
-    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, String> {
+    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, ValidationReport> {
         let first = payload
             .get("alerts")
             .and_then(serde_json::Value::as_array)
             .and_then(|a| a.first())
-            .ok_or_else(|| "alertmanager payload missing alerts[0]".to_string())?;
+            .ok_or_else(|| {
+                Violation::new(
+                    "/alerts/0",
+                    "required",
+                    "alertmanager payload has no alerts",
+                )
+            })?;
 
         let labels = first.get("labels").cloned().unwrap_or_else(|| serde_json::json!({}));
         let annotations = first
@@ -82,106 +177,642 @@ impl FactAdapter for AlertmanagerAdapter {
             .unwrap_or("unknown")
             .to_string();
 
-        let mut tags = Vec::new();
-        if let Some(obj) = labels.as_object() {
-            for (k, v) in obj {
-                if let Some(s) = v.as_str() {
-                    tags.push(format!("{k}:{s}"));
-                }
-            }
-        }
+        // Each alert, and the group, is `firing` or `resolved`; without a
+        // status, an `endsAt` already past means resolved, while a firing
+        // alert's is zero or still ahead.
+        let status = first
+            .get("status")
+            .or_else(|| payload.get("status"))
+            .and_then(serde_json::Value::as_str);
+        let resolved = match status {
+            Some(status) => status.eq_ignore_ascii_case("resolved"),
+            None => first
+                .get("endsAt")
+                .and_then(|ends_at| parse_timestamp(ends_at).ok())
+                .is_some_and(|ends_at| ends_at.timestamp() > 0 && ends_at <= Timestamp::now().0),
+        };
+        let tags = if resolved {
+            vec!["status:resolved".to_string()]
+        } else {
+            Vec::new()
+        };
 
-        let alert = CanonicalAlertV1 {
+        Ok(CanonicalAlertV1 {
             schema: "alert.v1".into(),
             id,
             title,
             severity,
             tags,
+            labels: string_map(&labels),
             source: "alertmanager".into(),
-            occurred_at: current_timestamp(),
+            occurred_at: source_time(first, &["startsAt"]),
+        })
+    }
+}
+
+impl FactAdapter for DatadogAdapter {
+    /// The alert's id is the notification's `aggregation_key`, shared by
+    /// every transition of one monitor group: a `Recovered` transition
+    /// resolves the incident, and a later re-trigger reopens it.
+    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, ValidationReport> {
+        let text = |key: &str| {
+            payload
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty() && !value.starts_with('$'))
         };
-        validate_alert_v1(&alert)?;
-        Ok(alert)
+
+        let aggregation_key = text("aggregation_key");
+        let id = aggregation_key
+            .or_else(|| text("alert_id"))
+            .or_else(|| text("id"))
+            .ok_or_else(|| {
+                Violation::new(
+                    "/aggregation_key",
+                    "required",
+                    "datadog payload has no aggregation_key, alert_id or id",
+                )
+            })?
+            .to_string();
+
+        let mut tags: Vec<String> = text("tags")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        let mut labels = labels_from_tags(&tags);
+        if let Some(key) = aggregation_key {
+            labels.insert("aggregation_key".into(), key.to_string());
+        }
+        if let Some(monitor) = text("alert_id") {
+            labels.insert("monitor_id".into(), monitor.to_string());
+        }
+        let transition = text("alert_transition").unwrap_or_default();
+        if transition.eq_ignore_ascii_case("recovered") {
+            tags.push("status:resolved".into());
+        }
+
+        Ok(CanonicalAlertV1 {
+            schema: "alert.v1".into(),
+            id,
+            title: text("event_title")
+                .or_else(|| text("title"))
+                .unwrap_or_default()
+                .to_string(),
+            severity: datadog_severity(
+                text("alert_priority").or_else(|| text("priority")),
+                text("alert_type"),
+            )
+            .into(),
+            tags,
+            labels,
+            source: "datadog".into(),
+            occurred_at: source_time(payload, &["date", "last_updated"]),
+        })
     }
 }
 
-pub fn webhook_router(tx: std::sync::mpsc::Sender<Fact>) -> Router {
-    Router::new()
-        .route("/webhook/generic", post(handle_generic))
-        .route("/webhook/datadog", post(handle_datadog))
-        .route("/webhook/pagerduty", post(handle_pagerduty))
-        .route("/webhook/alertmanager", post(handle_alertmanager))
-        .with_state(tx)
+impl PagerDutyAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The adapter with the secrets in `PAGERDUTY_WEBHOOK_SECRET`, comma
+    /// separated so a subscription's secret can be rotated.
+    pub fn from_env() -> Self {
+        std::env::var("PAGERDUTY_WEBHOOK_SECRET")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|secret| !secret.is_empty())
+            .fold(Self::new(), Self::secret)
+    }
+
+    /// Accept deliveries signed with `secret`.
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secrets.push(secret.into());
+        self
+    }
 }
 
-pub fn parse_generic(payload: &serde_json::Value) -> Result<Fact, String> {
-    parse_with_adapter(payload, GenericAdapter, AlertSource::Generic)
+impl FactAdapter for PagerDutyAdapter {
+    fn parse(&self, payload: &serde_json::Value) -> Result<CanonicalAlertV1, ValidationReport> {
+        self.parse_all(payload)?.pop().ok_or_else(|| {
+            Violation::new(
+                "/event/event_type",
+                "unsupported_event",
+                "not an incident event this adapter reads",
+            )
+            .into()
+        })
+    }
+
+    /// `X-PagerDuty-Signature` holds one `v1=<hex HMAC-SHA256 of the body>`
+    /// per secret on the subscription; one of them must match one of ours.
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), String> {
+        if self.secrets.is_empty() {
+            return Ok(());
+        }
+        let header = headers
+            .get("x-pagerduty-signature")
+            .ok_or("missing X-PagerDuty-Signature header")?
+            .to_str()
+            .map_err(|_| "unreadable X-PagerDuty-Signature header")?;
+        let signatures: Vec<Vec<u8>> = header
+            .split(',')
+            .filter_map(|signature| signature.trim().strip_prefix("v1="))
+            .filter_map(|signature| hex::decode(signature).ok())
+            .collect();
+        let signed = self.secrets.iter().any(|secret| {
+            signatures.iter().any(|signature| {
+                let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+                    return false;
+                };
+                mac.update(body);
+                mac.verify_slice(signature).is_ok()
+            })
+        });
+        if signed {
+            Ok(())
+        } else {
+            Err("X-PagerDuty-Signature matches no configured secret".into())
+        }
+    }
+
+    /// Triggered, acknowledged, escalated and resolved incidents, keyed by
+    /// the PagerDuty incident id so each event revises the one incident.
+    /// Other events, such as `pagey.ping`, carry no alert.
+    fn parse_all(
+        &self,
+        payload: &serde_json::Value,
+    ) -> Result<Vec<CanonicalAlertV1>, ValidationReport> {
+        let event = payload.get("event").ok_or_else(|| {
+            Violation::new("/event", "required", "pagerduty payload has no event")
+        })?;
+        let event_type = event
+            .get("event_type")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        if !matches!(
+            event_type,
+            "incident.triggered"
+                | "incident.acknowledged"
+                | "incident.escalated"
+                | "incident.resolved"
+        ) {
+            return Ok(Vec::new());
+        }
+
+        let data = event.get("data").unwrap_or(&serde_json::Value::Null);
+        let text = |pointer: &str| {
+            data.pointer(pointer)
+                .and_then(serde_json::Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let id = text("/id").ok_or_else(|| {
+            Violation::new("/event/data/id", "required", "incident event has no id")
+        })?;
+
+        let mut tags = vec![format!("pagerduty_event:{event_type}")];
+        if event_type == "incident.resolved" {
+            tags.push("status:resolved".into());
+        }
+        let labels = [
+            ("pagerduty_service", text("/service/summary")),
+            ("incident_key", text("/incident_key")),
+            ("urgency", text("/urgency")),
+            ("priority", text("/priority/summary")),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value?.to_string())))
+        .collect();
+
+        Ok(vec![CanonicalAlertV1 {
+            schema: "alert.v1".into(),
+            id: id.to_string(),
+            title: text("/title").unwrap_or_default().to_string(),
+            severity: pagerduty_severity(text("/priority/summary"), text("/urgency")).into(),
+            tags,
+            labels,
+            source: "pagerduty".into(),
+            occurred_at: source_time(event, &["occurred_at"]),
+        }])
+    }
 }
 
-fn parse_datadog(payload: &serde_json::Value) -> Result<Fact, String> {
-    parse_with_adapter(payload, GenericAdapter, AlertSource::Datadog)
+/// The incident's priority when the account uses priorities, else its
+/// urgency.
+fn pagerduty_severity(priority: Option<&str>, urgency: Option<&str>) -> &'static str {
+    match priority.unwrap_or_default().to_lowercase().as_str() {
+        "p1" => return "critical",
+        "p2" => return "high",
+        "p3" => return "medium",
+        "p4" | "p5" => return "low",
+        _ => {}
+    }
+    match urgency.unwrap_or_default() {
+        "low" => "low",
+        _ => "high",
+    }
 }
 
-fn parse_pagerduty(payload: &serde_json::Value) -> Result<Fact, String> {
-    parse_with_adapter(payload, GenericAdapter, AlertSource::PagerDuty)
+/// A `P1`–`P5` monitor priority when the template sends one, as
+/// `alert_priority` or `priority`, else the `alert_type`. Datadog's event
+/// priority `normal` says nothing about severity; `low` marks the event as
+/// minor.
+fn datadog_severity(priority: Option<&str>, alert_type: Option<&str>) -> &'static str {
+    let priority = priority.unwrap_or_default().to_lowercase();
+    match priority.as_str() {
+        "p1" => return "critical",
+        "p2" => return "high",
+        "p3" => return "medium",
+        "p4" | "p5" | "low" => return "low",
+        _ => {}
+    }
+    match alert_type.unwrap_or_default().to_lowercase().as_str() {
+        "warning" => "medium",
+        "info" | "success" => "low",
+        _ => "high",
+    }
 }
 
-fn parse_alertmanager(payload: &serde_json::Value) -> Result<Fact, String> {
-    parse_with_adapter(payload, AlertmanagerAdapter, AlertSource::Generic)
+/// The string-valued entries of a JSON object; the registry normalizes the
+/// keys on upgrade.
+fn string_map(value: &serde_json::Value) -> BTreeMap<String, String> {
+    value
+        .as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-async fn handle_generic(
-    State(tx): State<std::sync::mpsc::Sender<Fact>>,
-    Json(payload): Json<serde_json::Value>,
-) -> StatusCode {
-    send_fact(&tx, parse_generic(&payload))
+/// Adapters by source name, serving `POST /webhook/{source}`. Downstream
+/// crates start from [`AdapterRegistry::builtin`] and register their own
+/// sources before building the router with [`adapter_router`].
+#[derive(Clone, Default)]
+pub struct AdapterRegistry {
+    adapters: BTreeMap<String, Arc<Registered>>,
+    subscriptions: Option<std::sync::mpsc::Sender<SubscriptionRequest>>,
 }
 
-async fn handle_datadog(
-    State(tx): State<std::sync::mpsc::Sender<Fact>>,
-    Json(payload): Json<serde_json::Value>,
-) -> StatusCode {
-    send_fact(&tx, parse_datadog(&payload))
+struct Registered {
+    adapter: Box<dyn FactAdapter>,
+    source: AlertSource,
 }
 
-async fn handle_pagerduty(
-    State(tx): State<std::sync::mpsc::Sender<Fact>>,
-    Json(payload): Json<serde_json::Value>,
-) -> StatusCode {
-    send_fact(&tx, parse_pagerduty(&payload))
+impl AdapterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sources served out of the box: `generic`, `datadog`, `pagerduty`
+    /// and `alertmanager`. PagerDuty deliveries are verified against
+    /// `PAGERDUTY_WEBHOOK_SECRET` when it is set.
+    pub fn builtin() -> Self {
+        Self::new()
+            .register("generic", AlertSource::Generic, GenericAdapter)
+            .register("datadog", AlertSource::Datadog, DatadogAdapter)
+            .register(
+                "pagerduty",
+                AlertSource::PagerDuty,
+                PagerDutyAdapter::from_env(),
+            )
+            .register("alertmanager", AlertSource::Generic, AlertmanagerAdapter)
+    }
+
+    /// Serve `adapter` at `/webhook/{name}`, replacing any adapter already
+    /// registered under that name. Its alerts are tagged with `source`.
+    pub fn register(
+        mut self,
+        name: impl Into<String>,
+        source: AlertSource,
+        adapter: impl FactAdapter,
+    ) -> Self {
+        self.adapters.insert(
+            name.into(),
+            Arc::new(Registered {
+                adapter: Box::new(adapter),
+                source,
+            }),
+        );
+        self
+    }
+
+    /// Send the subscription requests sources deliver to `tx`, for the
+    /// caller to confirm or log. Without it they are answered and dropped.
+    pub fn subscriptions(mut self, tx: std::sync::mpsc::Sender<SubscriptionRequest>) -> Self {
+        self.subscriptions = Some(tx);
+        self
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        self.adapters.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.adapters.contains_key(name)
+    }
+
+    /// A delivery's raw body as facts through the adapter registered as
+    /// `name`, once the adapter has verified it, or `None` when no adapter
+    /// is registered.
+    pub fn receive(
+        &self,
+        name: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<Result<Validated<Vec<Fact>>, Rejected>> {
+        let registered = self.adapters.get(name)?;
+        if let Err(message) = registered.adapter.verify(headers, body) {
+            return Some(Err(Rejected::unauthorized(
+                Violation::new("", "bad_signature", message).into(),
+            )));
+        }
+        let payload: serde_json::Value = match serde_json::from_slice(body) {
+            Ok(payload) => payload,
+            Err(err) => {
+                return Some(Err(Rejected::malformed(
+                    Violation::new("", "invalid_json", err.to_string()).into(),
+                )));
+            }
+        };
+        self.parse(name, &payload)
+    }
+
+    /// `payload` as facts through the adapter registered as `name`, or
+    /// `None` when no adapter is. A subscription request has no facts.
+    pub fn parse(
+        &self,
+        name: &str,
+        payload: &serde_json::Value,
+    ) -> Option<Result<Validated<Vec<Fact>>, Rejected>> {
+        let registered = self.adapters.get(name)?;
+        if let Some(url) = registered.adapter.subscription(payload) {
+            if let Some(tx) = &self.subscriptions {
+                let _ = tx.send(SubscriptionRequest {
+                    source: name.to_string(),
+                    url,
+                });
+            }
+            return Some(Ok(Validated {
+                value: Vec::new(),
+                warnings: Vec::new(),
+            }));
+        }
+        Some(parse_with_adapter(
+            payload,
+            registered.adapter.as_ref(),
+            registered.source.clone(),
+        ))
+    }
+}
+
+#[derive(Clone)]
+struct WebhookState {
+    adapters: Arc<AdapterRegistry>,
+    tx: std::sync::mpsc::Sender<Fact>,
+}
+
+/// The webhooks for the [`AdapterRegistry::builtin`] sources.
+pub fn webhook_router(tx: std::sync::mpsc::Sender<Fact>) -> Router {
+    adapter_router(AdapterRegistry::builtin(), tx)
+}
+
+/// `POST /webhook/{source}` for every source in `adapters`; unknown sources
+/// get a 404.
+pub fn adapter_router(adapters: AdapterRegistry, tx: std::sync::mpsc::Sender<Fact>) -> Router {
+    Router::new()
+        .route("/webhook/:source", post(handle_webhook))
+        .with_state(WebhookState {
+            adapters: Arc::new(adapters),
+            tx,
+        })
 }
 
-async fn handle_alertmanager(
-    State(tx): State<std::sync::mpsc::Sender<Fact>>,
-    Json(payload): Json<serde_json::Value>,
-) -> StatusCode {
-    send_fact(&tx, parse_alertmanager(&payload))
+pub fn parse_generic(payload: &serde_json::Value) -> Result<Validated<Vec<Fact>>, Rejected> {
+    parse_with_adapter(payload, &GenericAdapter, AlertSource::Generic)
 }
 
+async fn handle_webhook(
+    State(state): State<WebhookState>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<ValidationReport>) {
+    match state.adapters.receive(&source, &headers, &body) {
+        Some(facts) => send_facts(&state.tx, facts),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(
+                Violation::new(
+                    "",
+                    "unknown_source",
+                    format!("no adapter for source '{source}'"),
+                )
+                .into(),
+            ),
+        ),
+    }
+}
+
+/// Payloads that declare a `schema` are taken as one canonical fact of any
+/// kind and validated against that version; anything else goes through
+/// `adapter`, which may find several alerts in it. Either way each canonical
+/// fact is upgraded to the latest version.
 fn parse_with_adapter(
     payload: &serde_json::Value,
-    adapter: impl FactAdapter,
+    adapter: &dyn FactAdapter,
     source: AlertSource,
-) -> Result<Fact, String> {
-    let canonical = adapter.parse(payload)?;
+) -> Result<Validated<Vec<Fact>>, Rejected> {
+    let canonical = if payload.get("schema").is_some() {
+        vec![payload.clone()]
+    } else {
+        adapter
+            .parse_all(payload)
+            .map_err(Rejected::malformed)?
+            .into_iter()
+            .map(|alert| serde_json::to_value(alert).map_err(|e| unreadable(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let mut facts = Validated {
+        value: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for payload in canonical {
+        let fact = canonical_fact(payload, source.clone())?;
+        facts.warnings.extend(fact.warnings);
+        facts.value.push(fact.value);
+    }
+    Ok(facts)
+}
+
+fn canonical_fact(
+    canonical: serde_json::Value,
+    source: AlertSource,
+) -> Result<Validated<Fact>, Rejected> {
+    let received_at = canonical.get("received_at").is_none().then(Timestamp::now);
+    let mut upgraded = registry().upgrade(canonical).map_err(Rejected::invalid)?;
+    let declared = upgraded.value["schema"].as_str().unwrap_or_default();
+    let kind = SchemaId::parse(declared).map_err(unreadable)?.kind;
+    let warnings = upgraded.warnings;
+    let fact = match kind.as_str() {
+        "alert" => {
+            // Migrations cannot know when an alert arrived; ingest does.
+            if let Some(received_at) = received_at {
+                upgraded.value["received_at"] = serde_json::json!(received_at);
+            }
+            alert_fact(upgraded.value, source)
+        }
+        _ => infra_fact(&kind, upgraded.value),
+    }
+    .map_err(unreadable)?;
+    Ok(Validated {
+        value: fact,
+        warnings,
+    })
+}
+
+/// A validated payload that still could not be turned into a fact.
+fn unreadable(message: String) -> Rejected {
+    Rejected::invalid(Violation::new("", "invalid", message).into())
+}
+
+fn alert_fact(upgraded: serde_json::Value, source: AlertSource) -> Result<Fact, String> {
+    let alert: CanonicalAlertV3 = serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+    let mut tags = alert.tags;
+    if alert.status == "resolved" && !tags.iter().any(|t| t == "status:resolved") {
+        tags.push("status:resolved".into());
+    }
     Ok(Fact::Alert(AlertFact {
-        id: canonical.id,
+        id: alert.id,
         source,
-        severity: map_severity(&canonical.severity),
-        title: canonical.title,
-        tags: canonical.tags,
-        received_at: canonical.occurred_at,
+        severity: map_severity(&alert.severity),
+        title: alert.title,
+        tags,
+        labels: alert.labels,
+        received_at: alert.received_at.0.timestamp().to_string(),
+        occurred_at: Some(alert.occurred_at.0),
     }))
 }
 
-fn send_fact(tx: &std::sync::mpsc::Sender<Fact>, fact: Result<Fact, String>) -> StatusCode {
-    let Ok(fact) = fact else {
-        return StatusCode::BAD_REQUEST;
+/// An infrastructure fact from its validated, latest-version payload.
+fn infra_fact(kind: &str, upgraded: serde_json::Value) -> Result<Fact, String> {
+    fn count(value: i64) -> Result<u32, String> {
+        u32::try_from(value).map_err(|_| format!("count {value} is out of range"))
+    }
+
+    Ok(match kind {
+        "pod" => {
+            let pod: CanonicalPodV1 =
+                serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+            Fact::Pod(PodFact {
+                id: format!("pod/{}/{}", pod.namespace, pod.name),
+                restarts: count(pod.restarts)?,
+                name: pod.name,
+                namespace: pod.namespace,
+                service: pod.service,
+                phase: pod.phase,
+                ready: pod.ready,
+                reason: pod.reason,
+                observed_at: pod.observed_at.0.timestamp().to_string(),
+            })
+        }
+        "deploy" => {
+            let deploy: CanonicalDeployV1 =
+                serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+            Fact::Deploy(DeployFact {
+                id: format!("deploy/{}/{}", deploy.namespace, deploy.name),
+                replicas: count(deploy.replicas)?,
+                available_replicas: count(deploy.available_replicas)?,
+                name: deploy.name,
+                namespace: deploy.namespace,
+                service: deploy.service,
+                version: deploy.version,
+                observed_at: deploy.observed_at.0.timestamp().to_string(),
+            })
+        }
+        "metric" => {
+            let metric: CanonicalMetricV1 =
+                serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+            Fact::Metric(MetricFact {
+                id: format!("metric/{}/{}", metric.service, metric.name),
+                service: metric.service,
+                name: metric.name,
+                value: metric.value,
+                unit: metric.unit,
+                threshold: metric.threshold,
+                observed_at: metric.observed_at.0.timestamp().to_string(),
+            })
+        }
+        "log" => {
+            let log: CanonicalLogV1 =
+                serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+            // Lines logged in the same second differ by time or content.
+            let digest = hex::encode(Sha256::digest(log.message.as_bytes()));
+            Fact::Log(LogFact {
+                id: format!(
+                    "log/{}/{}/{}",
+                    log.service,
+                    log.observed_at.0.timestamp_nanos_opt().unwrap_or_default(),
+                    &digest[..12]
+                ),
+                service: log.service,
+                level: log.level,
+                message: log.message,
+                observed_at: log.observed_at.0.timestamp().to_string(),
+            })
+        }
+        "change" => {
+            let change: CanonicalChangeV1 =
+                serde_json::from_value(upgraded).map_err(|e| e.to_string())?;
+            Fact::Change(ChangeFact {
+                id: change.id,
+                service: change.service,
+                kind: change.kind,
+                description: change.description,
+                author: change.author,
+                occurred_at: change.occurred_at.0.timestamp().to_string(),
+            })
+        }
+        other => return Err(format!("no fact for schema kind '{other}'")),
+    })
+}
+
+/// Answer with the validation report: its errors when the payload was
+/// rejected, else any warnings alongside the accepted facts.
+///
+/// 202 once the facts are queued, or 200 for a delivery that carried none,
+/// such as a subscription confirmation.
+fn send_facts(
+    tx: &std::sync::mpsc::Sender<Fact>,
+    facts: Result<Validated<Vec<Fact>>, Rejected>,
+) -> (StatusCode, Json<ValidationReport>) {
+    let facts = match facts {
+        Ok(facts) => facts,
+        Err(rejected) => return (rejected.status, Json(rejected.report)),
+    };
+    let status = if facts.value.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::ACCEPTED
+    };
+    let report = ValidationReport {
+        errors: Vec::new(),
+        warnings: facts.warnings,
     };
-    match tx.send(fact) {
-        Ok(_) => StatusCode::ACCEPTED,
-        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    for fact in facts.value {
+        if tx.send(fact).is_err() {
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(report));
+        }
     }
+    (status, Json(report))
 }
 
 fn map_severity(value: &str) -> Severity {
@@ -193,10 +824,336 @@ fn map_severity(value: &str) -> Severity {
     }
 }
 
-fn current_timestamp() -> String {
+/// When the source says the event happened, under the first of `keys` it
+/// sets, as sent; the registry parses and checks it. Defaults to now.
+fn source_time(payload: &serde_json::Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| match payload.get(*key)? {
+            serde_json::Value::String(time) => Some(time.clone()),
+            serde_json::Value::Number(epoch) => Some(epoch.to_string()),
+            _ => None,
+        })
+        .unwrap_or_else(current_timestamp)
+}
+
+pub(crate) fn current_timestamp() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) else {
         return "0".into();
     };
     duration.as_secs().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(source: &str, payload: &str) -> AlertFact {
+        let payload: serde_json::Value = serde_json::from_str(payload).expect("fixture");
+        let mut facts = AdapterRegistry::builtin()
+            .parse(source, &payload)
+            .expect("registered")
+            .expect("accepted")
+            .value;
+        match facts.pop() {
+            Some(Fact::Alert(alert)) if facts.is_empty() => alert,
+            other => panic!("expected one alert, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_sources_have_no_adapter() {
+        let registry = AdapterRegistry::builtin();
+        assert!(registry.parse("nagios", &serde_json::json!({})).is_none());
+        assert_eq!(
+            registry.sources().collect::<Vec<_>>(),
+            vec!["alertmanager", "datadog", "generic", "pagerduty"]
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_sources_are_answered_not_found() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let response = webhook_router(tx)
+            .oneshot(
+                Request::post("/webhook/nagios")
+                    .body(Body::from("{}"))
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        let report: ValidationReport = serde_json::from_slice(&body).expect("report");
+        assert_eq!(report.errors[0].code, "unknown_source");
+    }
+
+    #[test]
+    fn datadog_recoveries_resolve_the_triggered_incident() {
+        let triggered = alert(
+            "datadog",
+            include_str!("../fixtures/datadog-triggered.json"),
+        );
+        let recovered = alert(
+            "datadog",
+            include_str!("../fixtures/datadog-recovered.json"),
+        );
+
+        assert_eq!(triggered.id, "2b4f0bfa6a3b9f1e8c2d1a7b5e6f9c03");
+        assert_eq!(triggered.source, AlertSource::Datadog);
+        assert_eq!(triggered.severity, Severity::High);
+        assert!(triggered.title.contains("Checkout pods restarting"));
+        assert_eq!(triggered.labels["namespace"], "shop");
+        assert_eq!(triggered.labels["deployment"], "checkout-api");
+        assert_eq!(triggered.labels["monitor_id"], "148239576");
+        assert_eq!(
+            triggered.occurred_at.map(|t| t.timestamp()),
+            Some(1_736_510_400)
+        );
+        // Received at ingest, not when the monitor fired.
+        let received_at: i64 = triggered.received_at.parse().expect("epoch seconds");
+        assert!(received_at > 1_736_510_400);
+        assert!(!triggered.tags.iter().any(|t| t == "status:resolved"));
+
+        assert_eq!(recovered.id, triggered.id);
+        assert!(recovered.tags.iter().any(|t| t == "status:resolved"));
+        let beliefs = agent_core::rules::derive_beliefs(&[
+            Fact::Alert(triggered.clone()),
+            Fact::Alert(recovered.clone()),
+        ]);
+        assert!(beliefs.holds("alert_resolved", &[&triggered.id]));
+        assert!(beliefs.holds("service", &[&triggered.id, "checkout"]));
+
+        let retriggered = alert(
+            "datadog",
+            include_str!("../fixtures/datadog-triggered.json"),
+        );
+        let beliefs = agent_core::rules::derive_beliefs(&[
+            Fact::Alert(triggered.clone()),
+            Fact::Alert(recovered),
+            Fact::Alert(retriggered),
+        ]);
+        assert!(!beliefs.holds("alert_resolved", &[&triggered.id]));
+    }
+
+    #[test]
+    fn sns_confirmations_are_answered_ok_without_facts() {
+        let mapping = crate::mapping::FieldMapping::from_toml(include_str!(
+            "../adapters/cloudwatch-sns.toml"
+        ))
+        .expect("mapping");
+        let (subscriptions_tx, subscriptions) = std::sync::mpsc::channel();
+        let registry = AdapterRegistry::new()
+            .register(
+                "cloudwatch-sns",
+                AlertSource::Mapped("cloudwatch-sns".into()),
+                mapping,
+            )
+            .subscriptions(subscriptions_tx);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let status = |body: &str| {
+            let facts = registry
+                .receive("cloudwatch-sns", &HeaderMap::new(), body.as_bytes())
+                .expect("registered");
+            send_facts(&tx, facts).0
+        };
+
+        assert_eq!(
+            status(include_str!("../fixtures/cloudwatch-sns-confirmation.json")),
+            StatusCode::OK
+        );
+        let request = subscriptions.try_recv().expect("subscription request");
+        assert_eq!(request.source, "cloudwatch-sns");
+        assert!(request.url.contains("Action=ConfirmSubscription"));
+        assert_eq!(
+            status(include_str!("../fixtures/cloudwatch-sns.json")),
+            StatusCode::ACCEPTED
+        );
+        assert_eq!(rx.try_iter().count(), 1);
+        assert!(subscriptions.try_recv().is_err());
+    }
+
+    #[test]
+    fn deliveries_are_answered_with_their_validation_report() {
+        let registry = AdapterRegistry::builtin();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let answer = |body: &str| {
+            let facts = registry
+                .receive("generic", &HeaderMap::new(), body.as_bytes())
+                .expect("registered");
+            let (status, Json(report)) = send_facts(&tx, facts);
+            let codes = |violations: &[Violation]| -> Vec<(String, String)> {
+                violations
+                    .iter()
+                    .map(|v| (v.pointer.clone(), v.code.clone()))
+                    .collect()
+            };
+            (status, codes(&report.errors), codes(&report.warnings))
+        };
+        let pair = |pointer: &str, code: &str| (pointer.to_string(), code.to_string());
+
+        let (status, errors, _) = answer("{\"title\": ");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(errors, vec![pair("", "invalid_json")]);
+
+        let (status, errors, _) = answer(
+            r#"{"schema": "alert.v1", "id": "", "title": "", "severity": "high",
+                "source": "generic", "occurred_at": "1"}"#,
+        );
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            errors,
+            vec![
+                pair("/tags", "required"),
+                pair("/id", "min_length"),
+                pair("/title", "min_length"),
+            ]
+        );
+
+        let (status, errors, warnings) =
+            answer(r#"{"id": "a-1", "title": "cpu high", "severity": "sev1", "tags": []}"#);
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(errors.is_empty());
+        assert_eq!(warnings, vec![pair("/severity", "severity_coerced")]);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn log_lines_in_one_second_are_distinct_facts() {
+        let log = |message: &str| {
+            let payload = serde_json::json!({
+                "schema": "log.v1",
+                "service": "checkout",
+                "level": "error",
+                "message": message,
+                "observed_at": "2026-03-01T12:00:00.250Z"
+            });
+            match AdapterRegistry::builtin()
+                .parse("generic", &payload)
+                .expect("registered")
+                .expect("accepted")
+                .value
+                .pop()
+            {
+                Some(Fact::Log(log)) => log,
+                other => panic!("expected a log, got {other:?}"),
+            }
+        };
+        let refused = log("connection refused");
+        let timed_out = log("upstream timed out");
+
+        assert_ne!(refused.id, timed_out.id);
+        assert!(refused.id.starts_with("log/checkout/"));
+        assert_eq!(refused.observed_at, "1772366400");
+    }
+
+    #[test]
+    fn alertmanager_resolutions_resolve_the_incident() {
+        let delivery = |status: Option<&str>, ends_at: &str| {
+            let mut first = serde_json::json!({
+                "labels": {"alertname": "KubePodCrashLooping", "namespace": "shop"},
+                "annotations": {"summary": "checkout pods restarting"},
+                "startsAt": "2026-03-01T12:00:00Z",
+                "endsAt": ends_at,
+                "fingerprint": "c0ffee"
+            });
+            if let Some(status) = status {
+                first["status"] = serde_json::json!(status);
+            }
+            serde_json::json!({"alerts": [first]}).to_string()
+        };
+        let resolved = |alert: AlertFact| alert.tags.iter().any(|t| t == "status:resolved");
+
+        let firing = alert(
+            "alertmanager",
+            &delivery(Some("firing"), "0001-01-01T00:00:00Z"),
+        );
+        assert_eq!(firing.id, "c0ffee");
+        assert!(!resolved(firing));
+        assert!(resolved(alert(
+            "alertmanager",
+            &delivery(Some("resolved"), "2026-03-01T12:30:00Z"),
+        )));
+        assert!(resolved(alert(
+            "alertmanager",
+            &delivery(None, "2026-03-01T12:30:00Z")
+        )));
+        assert!(!resolved(alert(
+            "alertmanager",
+            &delivery(None, "0001-01-01T00:00:00Z")
+        )));
+    }
+
+    fn signed(secret: &str, body: &str) -> HeaderMap {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("key");
+        mac.update(body.as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-pagerduty-signature",
+            format!("v1=deadbeef, v1={signature}")
+                .parse()
+                .expect("header"),
+        );
+        headers
+    }
+
+    #[test]
+    fn pagerduty_incidents_map_priority_and_resolve() {
+        let triggered = alert(
+            "pagerduty",
+            include_str!("../fixtures/pagerduty-triggered.json"),
+        );
+        let resolved = alert(
+            "pagerduty",
+            include_str!("../fixtures/pagerduty-resolved.json"),
+        );
+
+        assert_eq!(triggered.id, "Q2QX1RNWY3NSB8");
+        assert_eq!(triggered.source, AlertSource::PagerDuty);
+        assert_eq!(triggered.severity, Severity::Critical);
+        assert_eq!(triggered.title, "Checkout 5xx rate above 5%");
+        assert_eq!(triggered.labels["incident_key"], "checkout-5xx-rate");
+        assert!(!triggered.tags.iter().any(|t| t == "status:resolved"));
+        assert_eq!(resolved.id, triggered.id);
+        assert!(resolved.tags.iter().any(|t| t == "status:resolved"));
+
+        assert_eq!(pagerduty_severity(None, Some("low")), "low");
+        assert_eq!(pagerduty_severity(Some("P3"), Some("high")), "medium");
+        let ping = serde_json::json!({"event": {"event_type": "pagey.ping", "data": {}}});
+        let facts = AdapterRegistry::builtin()
+            .parse("pagerduty", &ping)
+            .expect("registered")
+            .expect("accepted");
+        assert!(facts.value.is_empty());
+    }
+
+    #[test]
+    fn pagerduty_deliveries_must_be_signed_with_a_configured_secret() {
+        let registry = AdapterRegistry::new().register(
+            "pagerduty",
+            AlertSource::PagerDuty,
+            PagerDutyAdapter::new().secret("old").secret("current"),
+        );
+        let body = include_str!("../fixtures/pagerduty-triggered.json");
+        let receive = |headers: &HeaderMap| {
+            registry
+                .receive("pagerduty", headers, body.as_bytes())
+                .expect("registered")
+        };
+
+        let facts = receive(&signed("current", body)).expect("accepted");
+        assert_eq!(facts.value.len(), 1);
+        for headers in [signed("stolen", body), HeaderMap::new()] {
+            let rejected = receive(&headers).expect_err("rejected");
+            assert_eq!(rejected.status, StatusCode::UNAUTHORIZED);
+            assert_eq!(rejected.report.errors[0].code, "bad_signature");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Canonical infrastructure facts: the pods, deployments, metrics, logs and
+//! changes behind a service, so rules and planners can reason about more
+//! than an alert's title.
+
+use crate::json_schema::Violation;
+use crate::timestamp::Timestamp;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A pod's state as last observed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalPodV1 {
+    #[schemars(extend("const" = "pod.v1"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub name: String,
+    #[schemars(length(min = 1))]
+    pub namespace: String,
+    #[schemars(length(min = 1))]
+    pub service: String,
+    #[schemars(extend("enum" = ["pending", "running", "succeeded", "failed", "unknown"]))]
+    pub phase: String,
+    #[schemars(range(min = 0))]
+    pub restarts: i64,
+    pub ready: bool,
+    /// Why the last container stopped, such as `CrashLoopBackOff` or
+    /// `OOMKilled`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub observed_at: Timestamp,
+}
+
+/// A deployment's rollout state as last observed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalDeployV1 {
+    #[schemars(extend("const" = "deploy.v1"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub name: String,
+    #[schemars(length(min = 1))]
+    pub namespace: String,
+    #[schemars(length(min = 1))]
+    pub service: String,
+    pub version: String,
+    #[schemars(range(min = 0))]
+    pub replicas: i64,
+    #[schemars(range(min = 0))]
+    pub available_replicas: i64,
+    pub observed_at: Timestamp,
+}
+
+/// One sample of a service metric, with the threshold it alerts above.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalMetricV1 {
+    #[schemars(extend("const" = "metric.v1"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub service: String,
+    #[schemars(length(min = 1))]
+    pub name: String,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    pub observed_at: Timestamp,
+}
+
+/// A log line worth keeping as a fact.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalLogV1 {
+    #[schemars(extend("const" = "log.v1"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub service: String,
+    #[schemars(extend("enum" = ["debug", "info", "warn", "error", "fatal"]))]
+    pub level: String,
+    #[schemars(length(min = 1))]
+    pub message: String,
+    pub observed_at: Timestamp,
+}
+
+/// A change made to a service: a deploy, config push, scaling, rollback or
+/// feature flag flip.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalChangeV1 {
+    #[schemars(extend("const" = "change.v1"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub id: String,
+    #[schemars(length(min = 1))]
+    pub service: String,
+    #[schemars(extend("enum" = ["deploy", "config", "scale", "rollback", "feature_flag"]))]
+    pub kind: String,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub occurred_at: Timestamp,
+}
+
+pub fn pod_violations(pod: &CanonicalPodV1) -> Vec<Violation> {
+    let mut out = required(&[
+        ("/name", &pod.name),
+        ("/namespace", &pod.namespace),
+        ("/service", &pod.service),
+    ]);
+    out.extend(non_negative("/restarts", pod.restarts));
+    out
+}
+
+pub fn deploy_violations(deploy: &CanonicalDeployV1) -> Vec<Violation> {
+    let mut out = required(&[
+        ("/name", &deploy.name),
+        ("/namespace", &deploy.namespace),
+        ("/service", &deploy.service),
+    ]);
+    out.extend(non_negative("/replicas", deploy.replicas));
+    out.extend(non_negative(
+        "/available_replicas",
+        deploy.available_replicas,
+    ));
+    if deploy.available_replicas > deploy.replicas {
+        out.push(Violation::new(
+            "/available_replicas",
+            "exceeds_replicas",
+            format!(
+                "{} available of {} replicas",
+                deploy.available_replicas, deploy.replicas
+            ),
+        ));
+    }
+    out
+}
+
+pub fn metric_violations(metric: &CanonicalMetricV1) -> Vec<Violation> {
+    let mut out = required(&[("/service", &metric.service), ("/name", &metric.name)]);
+    out.extend(finite("/value", metric.value));
+    if let Some(threshold) = metric.threshold {
+        out.extend(finite("/threshold", threshold));
+    }
+    out
+}
+
+pub fn log_violations(log: &CanonicalLogV1) -> Vec<Violation> {
+    required(&[("/service", &log.service), ("/message", &log.message)])
+}
+
+pub fn change_violations(change: &CanonicalChangeV1) -> Vec<Violation> {
+    required(&[("/id", &change.id), ("/service", &change.service)])
+}
+
+/// Run typed checks on a payload the schema document already accepted.
+pub(crate) fn typed<T: DeserializeOwned>(
+    payload: &Value,
+    check: fn(&T) -> Vec<Violation>,
+) -> Vec<Violation> {
+    match serde_json::from_value(payload.clone()) {
+        Ok(fact) => check(&fact),
+        Err(err) => vec![Violation::new("", "invalid", err.to_string())],
+    }
+}
+
+fn required(fields: &[(&str, &String)]) -> Vec<Violation> {
+    fields
+        .iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(pointer, _)| Violation::new(*pointer, "blank", "must not be blank"))
+        .collect()
+}
+
+fn non_negative(pointer: &str, count: i64) -> Option<Violation> {
+    (count < 0).then(|| {
+        Violation::new(
+            pointer,
+            "negative",
+            format!("must not be negative, got {count}"),
+        )
+    })
+}
+
+fn finite(pointer: &str, value: f64) -> Option<Violation> {
+    (!value.is_finite()).then(|| {
+        Violation::new(
+            pointer,
+            "not_finite",
+            format!("must be finite, got {value}"),
+        )
+    })
+}
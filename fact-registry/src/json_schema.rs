@@ -0,0 +1,328 @@
+//! Validation of raw payloads against the JSON Schema documents generated
+//! for canonical types.
+//!
+//! Covers the keywords those documents use: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, string
+//! lengths, numeric bounds, `allOf`/`anyOf`/`oneOf` and local `$ref`s into
+//! `$defs`. Other keywords are ignored.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One way a payload fails its schema, at a JSON pointer into the payload.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    /// RFC 6901 pointer to the field; empty for the payload itself.
+    pub pointer: String,
+    /// Stable, machine-readable kind of problem, such as `required` or
+    /// `minimum`.
+    pub code: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(
+        pointer: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            pointer: pointer.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+/// Every violation of `schema` by `payload`.
+pub fn validate(schema: &Value, payload: &Value) -> Vec<Violation> {
+    let mut out = Vec::new();
+    check(schema, schema, payload, "", &mut out);
+    out
+}
+
+/// Append `token` to `pointer`, escaped per RFC 6901.
+pub fn child_pointer(pointer: &str, token: &str) -> String {
+    format!("{pointer}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, pointer: &str, out: &mut Vec<Violation>) {
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            out.push(Violation::new(
+                pointer,
+                "not_allowed",
+                "no value is allowed here",
+            ));
+        }
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => check(root, target, value, pointer, out),
+            None => out.push(Violation::new(
+                pointer,
+                "ref",
+                format!("unresolvable $ref '{reference}'"),
+            )),
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        if !type_matches(expected, value) {
+            out.push(Violation::new(
+                pointer,
+                "type",
+                format!(
+                    "expected {}, found {}",
+                    type_names(expected),
+                    type_of(value)
+                ),
+            ));
+            // Nothing below applies to a value of the wrong type.
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            out.push(Violation::new(
+                pointer,
+                "enum",
+                format!("must be one of {}", Value::Array(allowed.clone())),
+            ));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            out.push(Violation::new(
+                pointer,
+                "const",
+                format!("must be {constant}"),
+            ));
+        }
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            check(root, sub, value, pointer, out);
+        }
+    }
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+        if !any
+            .iter()
+            .any(|sub| validate_at(root, sub, value).is_empty())
+        {
+            out.push(Violation::new(
+                pointer,
+                "any_of",
+                "matches none of the allowed schemas",
+            ));
+        }
+    }
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let matched = one
+            .iter()
+            .filter(|sub| validate_at(root, sub, value).is_empty())
+            .count();
+        if matched != 1 {
+            out.push(Violation::new(
+                pointer,
+                "one_of",
+                format!("must match exactly one schema, matched {matched}"),
+            ));
+        }
+    }
+
+    match value {
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            let min = schema.get("minLength").and_then(Value::as_u64);
+            let max = schema.get("maxLength").and_then(Value::as_u64);
+            if let Some(min) = min.filter(|&min| len < min) {
+                out.push(Violation::new(
+                    pointer,
+                    "min_length",
+                    format!("must be at least {min} characters"),
+                ));
+            }
+            if let Some(max) = max.filter(|&max| len > max) {
+                out.push(Violation::new(
+                    pointer,
+                    "max_length",
+                    format!("must be at most {max} characters"),
+                ));
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            let min = schema.get("minimum").and_then(Value::as_f64);
+            let max = schema.get("maximum").and_then(Value::as_f64);
+            if let Some(min) = min.filter(|&min| n < min) {
+                out.push(Violation::new(
+                    pointer,
+                    "minimum",
+                    format!("must be at least {min}"),
+                ));
+            }
+            if let Some(max) = max.filter(|&max| n > max) {
+                out.push(Violation::new(
+                    pointer,
+                    "maximum",
+                    format!("must be at most {max}"),
+                ));
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(
+                        root,
+                        item_schema,
+                        item,
+                        &child_pointer(pointer, &i.to_string()),
+                        out,
+                    );
+                }
+            }
+        }
+        Value::Object(fields) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !fields.contains_key(name) {
+                        out.push(Violation::new(
+                            child_pointer(pointer, name),
+                            "required",
+                            "is required",
+                        ));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in fields {
+                let field_pointer = child_pointer(pointer, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(field_schema) => check(root, field_schema, field, &field_pointer, out),
+                    None => {
+                        if let Some(extra) = schema.get("additionalProperties") {
+                            check(root, extra, field, &field_pointer, out);
+                        }
+                    }
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+fn validate_at(root: &Value, schema: &Value, value: &Value) -> Vec<Violation> {
+    let mut out = Vec::new();
+    check(root, schema, value, "", &mut out);
+    out
+}
+
+/// A `#/...` pointer into the root schema.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn type_matches(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(name) => is_type(name, value),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| is_type(name, value)),
+        _ => true,
+    }
+}
+
+fn is_type(name: &str, value: &Value) -> bool {
+    match name {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some(),
+        "number" => value.is_number(),
+        other => other == type_of(value),
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_names(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or("any").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn collects_every_violation_with_pointers() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "tags", "owner"],
+            "properties": {
+                "id": {"type": "string", "minLength": 1},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "owner": {"$ref": "#/$defs/Owner"},
+                "a/b": {"enum": ["x"]}
+            },
+            "additionalProperties": false,
+            "$defs": {
+                "Owner": {
+                    "type": "object",
+                    "required": ["team"],
+                    "properties": {"team": {"type": ["string", "null"]}}
+                }
+            }
+        });
+        let violations = validate(
+            &schema,
+            &json!({"id": "", "tags": ["ok", 3], "owner": {}, "a/b": "y", "extra": 1}),
+        );
+        let mut pointers: Vec<&str> = violations.iter().map(|v| v.pointer.as_str()).collect();
+        pointers.sort_unstable();
+        assert_eq!(
+            pointers,
+            vec!["/a~1b", "/extra", "/id", "/owner/team", "/tags/1"]
+        );
+        assert!(violations
+            .iter()
+            .any(|v| v.to_string() == "/tags/1: expected string, found number"));
+        assert!(violations
+            .iter()
+            .any(|v| v.pointer == "/extra" && v.code == "not_allowed"));
+
+        assert!(validate(
+            &schema,
+            &json!({"id": "x", "tags": [], "owner": {"team": null}})
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Label normalization, so `ns`, `Namespace` and
+//! `app.kubernetes.io/namespace` all end up as `namespace`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Well-known spellings of the labels rules and runbooks bind, by the key
+/// they normalize to. Keys are compared after [`normalize_label_key`]'s
+/// lowercasing and punctuation folding.
+pub const LABEL_ALIASES: &[(&str, &str)] = &[
+    ("ns", "namespace"),
+    ("k8s_namespace", "namespace"),
+    ("kubernetes_namespace", "namespace"),
+    ("app_kubernetes_io_namespace", "namespace"),
+    ("kube_namespace", "namespace"),
+    ("app", "service"),
+    ("svc", "service"),
+    ("service_name", "service"),
+    ("app_kubernetes_io_name", "service"),
+    ("kube_service", "service"),
+    ("deploy", "deployment"),
+    ("deployment_name", "deployment"),
+    ("kubernetes_deployment", "deployment"),
+    ("kube_deployment", "deployment"),
+    ("pod_name", "pod"),
+    ("kubernetes_pod_name", "pod"),
+    ("env", "environment"),
+];
+
+/// Lowercase `key`, fold `.`, `-`, `/` and spaces to `_`, and resolve
+/// well-known aliases.
+pub fn normalize_label_key(key: &str) -> String {
+    let folded: String = key
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '.' | '-' | '/' | ' ' => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect();
+    LABEL_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == folded)
+        .map(|(_, key)| key.to_string())
+        .unwrap_or(folded)
+}
+
+/// `labels` with normalized keys and trimmed values. Empty keys and values
+/// are dropped; when several keys normalize alike, an exact spelling of the
+/// normalized key wins, else the last in key order.
+pub fn normalize_labels<'a>(
+    labels: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    let mut exact = BTreeSet::new();
+    for (key, value) in labels {
+        let normalized = normalize_label_key(key);
+        let value = value.trim();
+        if normalized.is_empty() || value.is_empty() || exact.contains(&normalized) {
+            continue;
+        }
+        if *key == normalized {
+            exact.insert(normalized.clone());
+        }
+        out.insert(normalized, value.to_string());
+    }
+    out
+}
+
+/// Labels carried as `key:value` tags.
+pub fn labels_from_tags(tags: &[String]) -> BTreeMap<String, String> {
+    let pairs: Vec<(String, String)> = tags
+        .iter()
+        .filter_map(|tag| tag.split_once(':'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    normalize_labels(pairs.iter().map(|(k, v)| (k, v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_keys_and_aliases() {
+        let raw: BTreeMap<String, String> = [
+            ("NS", " shop "),
+            ("app.kubernetes.io/name", "checkout"),
+            ("service", "checkout-api"),
+            ("Deployment-Name", "checkout-v2"),
+            ("team", ""),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let labels = normalize_labels(&raw);
+        assert_eq!(labels["namespace"], "shop");
+        assert_eq!(labels["service"], "checkout-api");
+        assert_eq!(labels["deployment"], "checkout-v2");
+        assert!(!labels.contains_key("team"));
+
+        let tags = vec!["ns:shop".to_string(), "crashloop".to_string()];
+        assert_eq!(
+            labels_from_tags(&tags),
+            BTreeMap::from([("namespace".to_string(), "shop".to_string())])
+        );
+    }
+}
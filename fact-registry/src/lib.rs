@@ -1,31 +1,333 @@
+//! Canonical fact schemas, versioned per kind in a [`SchemaRegistry`] with
+//! migrations that upgrade older payloads to the latest version, whether
+//! they arrive at ingest or are read back from stored events. Every schema
+//! has a JSON Schema document generated from its type, and payloads are
+//! validated against it, with every [`Violation`] and any warnings collected
+//! in a [`ValidationReport`].
+//!
+//! Besides alerts, the [`infra`] kinds describe the pods, deployments,
+//! metrics, logs and changes behind a service.
+
+pub mod infra;
+pub mod json_schema;
+pub mod labels;
+pub mod registry;
+pub mod report;
+pub mod timestamp;
+
+pub use infra::{
+    CanonicalChangeV1, CanonicalDeployV1, CanonicalLogV1, CanonicalMetricV1, CanonicalPodV1,
+};
+pub use json_schema::Violation;
+pub use labels::{labels_from_tags, normalize_label_key, normalize_labels};
+pub use registry::{describe, Migration, SchemaId, SchemaRegistry, Validator};
+pub use report::{Validated, ValidationReport};
+pub use timestamp::{parse_timestamp, Timestamp};
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CanonicalAlertV1 {
+    #[schemars(extend("const" = "alert.v1"))]
     pub schema: String,
+    #[schemars(length(min = 1))]
     pub id: String,
+    #[schemars(length(min = 1))]
     pub title: String,
+    /// low, medium, high or critical, in any case; anything else is read
+    /// as high, with a warning.
     pub severity: String,
     pub tags: Vec<String>,
+    /// Key-value labels such as `namespace` or `service`, normalized on
+    /// upgrade.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
     pub source: String,
     pub occurred_at: String,
 }
 
-pub fn validate_alert_v1(alert: &CanonicalAlertV1) -> Result<(), String> {
-    if alert.schema != "alert.v1" {
-        return Err(format!("unsupported schema '{}'", alert.schema));
+/// `alert.v1` plus whether the alert is still firing. Severity and status
+/// are lowercase.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalAlertV2 {
+    #[schemars(extend("const" = "alert.v2"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub id: String,
+    #[schemars(length(min = 1))]
+    pub title: String,
+    #[schemars(extend("enum" = ["low", "medium", "high", "critical"]))]
+    pub severity: String,
+    #[schemars(extend("enum" = ["firing", "resolved"]))]
+    pub status: String,
+    pub tags: Vec<String>,
+    /// Key-value labels such as `namespace` or `service`, normalized on
+    /// upgrade.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub source: String,
+    pub occurred_at: String,
+}
+
+/// `alert.v2` with typed event times: when the alert fired at its source,
+/// and when it was received here.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CanonicalAlertV3 {
+    #[schemars(extend("const" = "alert.v3"))]
+    pub schema: String,
+    #[schemars(length(min = 1))]
+    pub id: String,
+    #[schemars(length(min = 1))]
+    pub title: String,
+    #[schemars(extend("enum" = ["low", "medium", "high", "critical"]))]
+    pub severity: String,
+    #[schemars(extend("enum" = ["firing", "resolved"]))]
+    pub status: String,
+    pub tags: Vec<String>,
+    /// Key-value labels such as `namespace` or `service`, normalized on
+    /// upgrade.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub source: String,
+    pub occurred_at: Timestamp,
+    pub received_at: Timestamp,
+}
+
+/// `occurred_at` may be RFC 3339 or epoch seconds or milliseconds, and must
+/// not lie in the future.
+pub fn validate_alert_v1(alert: &CanonicalAlertV1) -> ValidationReport {
+    let mut report = schema_report(&alert.schema, "alert.v1");
+    report.merge(alert_v1_report(&alert.id, &alert.title, &alert.severity));
+    report.merge(timestamps_report(
+        &serde_json::json!({"occurred_at": alert.occurred_at}),
+        &["/occurred_at"],
+    ));
+    report
+}
+
+pub fn validate_alert_v2(alert: &CanonicalAlertV2) -> ValidationReport {
+    let mut report = schema_report(&alert.schema, "alert.v2");
+    report.merge(alert_v2_report(
+        &alert.id,
+        &alert.title,
+        &alert.severity,
+        &alert.status,
+    ));
+    report.merge(timestamps_report(
+        &serde_json::json!({"occurred_at": alert.occurred_at}),
+        &["/occurred_at"],
+    ));
+    report
+}
+
+pub fn validate_alert_v3(alert: &CanonicalAlertV3) -> ValidationReport {
+    let mut report = schema_report(&alert.schema, "alert.v3");
+    report.merge(alert_v2_report(
+        &alert.id,
+        &alert.title,
+        &alert.severity,
+        &alert.status,
+    ));
+    let times = serde_json::json!({
+        "occurred_at": alert.occurred_at,
+        "received_at": alert.received_at,
+    });
+    report.merge(timestamps_report(&times, &["/occurred_at", "/received_at"]));
+    report
+}
+
+/// Reject timestamps at `pointers` that do not parse or lie in the future.
+fn timestamps_report(payload: &Value, pointers: &[&str]) -> ValidationReport {
+    let now = Utc::now();
+    ValidationReport::rejecting(
+        pointers
+            .iter()
+            .filter_map(|pointer| timestamp::check_timestamp(payload, pointer, now).err())
+            .collect(),
+    )
+}
+
+/// Run an alert's typed `validate` on a payload the schema document already
+/// accepted. A payload that does not read as its type has an unparseable
+/// timestamp at one of `pointers`, reported there when it does.
+fn alert_report<T: DeserializeOwned>(
+    payload: &Value,
+    pointers: &[&str],
+    validate: fn(&T) -> ValidationReport,
+) -> ValidationReport {
+    match serde_json::from_value(payload.clone()) {
+        Ok(alert) => validate(&alert),
+        Err(err) => {
+            let report = timestamps_report(payload, pointers);
+            if report.is_valid() {
+                Violation::new("", "invalid", err.to_string()).into()
+            } else {
+                report
+            }
+        }
     }
-    if alert.id.trim().is_empty() {
-        return Err("id is required".into());
+}
+
+/// Reject an infrastructure payload whose timestamp at `pointer` does not
+/// parse or lies in the future, else run its typed `check`.
+fn infra_report<T: DeserializeOwned>(
+    payload: &Value,
+    pointer: &str,
+    check: fn(&T) -> Vec<Violation>,
+) -> ValidationReport {
+    let report = timestamps_report(payload, &[pointer]);
+    if !report.is_valid() {
+        return report;
     }
-    if alert.title.trim().is_empty() {
-        return Err("title is required".into());
+    ValidationReport::rejecting(infra::typed(payload, check))
+}
+
+fn schema_report(declared: &str, expected: &str) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    if declared != expected {
+        report = report.error(Violation::new(
+            "/schema",
+            "unsupported_schema",
+            format!("unsupported schema '{declared}'"),
+        ));
+    }
+    report
+}
+
+fn alert_v1_report(id: &str, title: &str, severity: &str) -> ValidationReport {
+    let mut report = ValidationReport::rejecting(required_alert_fields(id, title));
+    if known_severity(severity).is_none() {
+        report = report.warn(Violation::new(
+            "/severity",
+            "severity_coerced",
+            format!("unknown severity '{severity}' is read as 'high'"),
+        ));
+    }
+    report
+}
+
+fn alert_v2_report(id: &str, title: &str, severity: &str, status: &str) -> ValidationReport {
+    let mut report = ValidationReport::rejecting(required_alert_fields(id, title));
+    if !matches!(severity, "low" | "medium" | "high" | "critical") {
+        report = report.error(Violation::new(
+            "/severity",
+            "invalid_severity",
+            format!("invalid severity '{severity}'"),
+        ));
     }
-    match alert.severity.to_lowercase().as_str() {
-        "low" | "medium" | "high" | "critical" => {}
-        other => return Err(format!("invalid severity '{other}'")),
+    if !matches!(status, "firing" | "resolved") {
+        report = report.error(Violation::new(
+            "/status",
+            "invalid_status",
+            format!("invalid status '{status}'"),
+        ));
     }
-    Ok(())
+    report
+}
+
+fn required_alert_fields(id: &str, title: &str) -> Vec<Violation> {
+    let mut out = Vec::new();
+    if id.trim().is_empty() {
+        out.push(Violation::new("/id", "blank", "id is required"));
+    }
+    if title.trim().is_empty() {
+        out.push(Violation::new("/title", "blank", "title is required"));
+    }
+    out
+}
+
+/// `severity` lowercased, if it is one of the known severities.
+fn known_severity(severity: &str) -> Option<&'static str> {
+    match severity.to_lowercase().as_str() {
+        "low" => Some("low"),
+        "medium" => Some("medium"),
+        "high" => Some("high"),
+        "critical" => Some("critical"),
+        _ => None,
+    }
+}
+
+/// Lowercase the severity, reading unknown ones as `high`, derive `status`
+/// from a `status:resolved` tag, else `firing`, and normalize labels.
+pub fn migrate_alert_v1_to_v2(payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    let v1: CanonicalAlertV1 = serde_json::from_value(payload).map_err(|e| e.to_string())?;
+    let resolved = v1
+        .tags
+        .iter()
+        .any(|tag| tag.eq_ignore_ascii_case("status:resolved"));
+    let v2 = CanonicalAlertV2 {
+        schema: "alert.v2".into(),
+        id: v1.id,
+        title: v1.title,
+        severity: known_severity(&v1.severity).unwrap_or("high").into(),
+        status: if resolved { "resolved" } else { "firing" }.into(),
+        tags: v1.tags,
+        labels: normalize_labels(&v1.labels),
+        source: v1.source,
+        occurred_at: v1.occurred_at,
+    };
+    serde_json::to_value(v2).map_err(|e| e.to_string())
+}
+
+/// Parse `occurred_at` into a typed timestamp and normalize labels. Older
+/// alerts never recorded when they were received, so `received_at` starts
+/// as `occurred_at`; ingest stamps its own receipt time over it.
+pub fn migrate_alert_v2_to_v3(payload: Value) -> Result<Value, String> {
+    let v2: CanonicalAlertV2 = serde_json::from_value(payload).map_err(|e| e.to_string())?;
+    let occurred_at = Timestamp(parse_timestamp(&Value::String(v2.occurred_at))?);
+    let v3 = CanonicalAlertV3 {
+        schema: "alert.v3".into(),
+        id: v2.id,
+        title: v2.title,
+        severity: v2.severity,
+        status: v2.status,
+        tags: v2.tags,
+        labels: normalize_labels(&v2.labels),
+        source: v2.source,
+        occurred_at,
+        received_at: occurred_at,
+    };
+    serde_json::to_value(v3).map_err(|e| e.to_string())
+}
+
+/// Every canonical schema this crate defines, with their migrations.
+pub fn canonical_registry() -> SchemaRegistry {
+    SchemaRegistry::new()
+        .schema::<CanonicalAlertV1>("alert", 1, |payload| {
+            alert_report(payload, &["/occurred_at"], validate_alert_v1)
+        })
+        .schema::<CanonicalAlertV2>("alert", 2, |payload| {
+            alert_report(payload, &["/occurred_at"], validate_alert_v2)
+        })
+        .schema::<CanonicalAlertV3>("alert", 3, |payload| {
+            alert_report(
+                payload,
+                &["/occurred_at", "/received_at"],
+                validate_alert_v3,
+            )
+        })
+        .migration("alert", 1, migrate_alert_v1_to_v2)
+        .migration("alert", 2, migrate_alert_v2_to_v3)
+        .schema::<CanonicalPodV1>("pod", 1, |payload| {
+            infra_report(payload, "/observed_at", infra::pod_violations)
+        })
+        .schema::<CanonicalDeployV1>("deploy", 1, |payload| {
+            infra_report(payload, "/observed_at", infra::deploy_violations)
+        })
+        .schema::<CanonicalMetricV1>("metric", 1, |payload| {
+            infra_report(payload, "/observed_at", infra::metric_violations)
+        })
+        .schema::<CanonicalLogV1>("log", 1, |payload| {
+            infra_report(payload, "/observed_at", infra::log_violations)
+        })
+        .schema::<CanonicalChangeV1>("change", 1, |payload| {
+            infra_report(payload, "/occurred_at", infra::change_violations)
+        })
 }
 
 #[cfg(test)]
@@ -40,9 +342,257 @@ mod tests {
             title: "cpu high".into(),
             severity: "high".into(),
             tags: vec!["cpu".into()],
+            labels: BTreeMap::new(),
             source: "generic".into(),
             occurred_at: "1".into(),
         };
-        assert!(validate_alert_v1(&alert).is_ok());
+        assert_eq!(validate_alert_v1(&alert), ValidationReport::new());
+
+        let report = validate_alert_v1(&CanonicalAlertV1 {
+            id: " ".into(),
+            title: String::new(),
+            severity: "sev1".into(),
+            ..alert
+        });
+        let codes: Vec<(&str, &str)> = report
+            .errors
+            .iter()
+            .map(|v| (v.pointer.as_str(), v.code.as_str()))
+            .collect();
+        assert_eq!(codes, vec![("/id", "blank"), ("/title", "blank")]);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].code, "severity_coerced");
+    }
+
+    #[test]
+    fn upgrades_alert_v1_to_v2() {
+        let registry = canonical_registry();
+
+        let upgraded = registry
+            .upgrade_to(
+                serde_json::json!({
+                    "schema": "alert.v1",
+                    "id": "inc-1",
+                    "title": "cpu high",
+                    "severity": "HIGH",
+                    "tags": ["status:resolved"],
+                    "labels": {"NS": "shop", "app": "checkout"},
+                    "source": "generic",
+                    "occurred_at": "1"
+                }),
+                &SchemaId::new("alert", 2),
+            )
+            .expect("upgrade");
+        assert!(upgraded.warnings.is_empty());
+        let alert: CanonicalAlertV2 = serde_json::from_value(upgraded.value).expect("v2");
+        assert_eq!(alert.severity, "high");
+        assert_eq!(alert.status, "resolved");
+        assert_eq!(alert.labels["namespace"], "shop");
+        assert_eq!(alert.labels["service"], "checkout");
+
+        let coerced = registry
+            .upgrade(serde_json::json!({
+                "schema": "alert.v1",
+                "id": "inc-2",
+                "title": "cpu high",
+                "severity": "P1",
+                "tags": [],
+                "source": "generic",
+                "occurred_at": "1"
+            }))
+            .expect("upgrade");
+        assert_eq!(coerced.value["severity"], "high");
+        assert_eq!(coerced.warnings[0].pointer, "/severity");
+
+        let violations = registry
+            .validate(&serde_json::json!({
+                "schema": "alert.v2",
+                "id": "",
+                "title": "cpu high",
+                "severity": "HIGH",
+                "status": "acked",
+                "tags": [1],
+                "occurred_at": "1"
+            }))
+            .expect_err("invalid alert");
+        let mut pointers: Vec<&str> = violations
+            .errors
+            .iter()
+            .map(|v| v.pointer.as_str())
+            .collect();
+        pointers.sort_unstable();
+        assert_eq!(
+            pointers,
+            vec!["/id", "/severity", "/source", "/status", "/tags/0"]
+        );
+
+        let document = registry
+            .document(&SchemaId::new("alert", 2))
+            .expect("document");
+        assert_eq!(document["$id"], "alert.v2");
+        assert_eq!(
+            document["properties"]["status"]["enum"],
+            serde_json::json!(["firing", "resolved"])
+        );
+    }
+
+    #[test]
+    fn upgrades_alert_v2_to_v3_with_typed_times() {
+        let registry = canonical_registry();
+        assert_eq!(registry.latest("alert"), Some(SchemaId::new("alert", 3)));
+
+        let upgraded = registry
+            .upgrade(serde_json::json!({
+                "schema": "alert.v2",
+                "id": "inc-1",
+                "title": "cpu high",
+                "severity": "high",
+                "status": "firing",
+                "tags": [],
+                "source": "alertmanager",
+                "occurred_at": "2026-03-01T13:00:00+01:00"
+            }))
+            .expect("upgrade");
+        assert_eq!(upgraded.value["occurred_at"], "2026-03-01T12:00:00Z");
+        let alert: CanonicalAlertV3 = serde_json::from_value(upgraded.value).expect("v3");
+        assert_eq!(alert.received_at.0, alert.occurred_at.0);
+        assert!(validate_alert_v3(&alert).is_valid());
+
+        let epoch = |occurred_at: serde_json::Value| {
+            registry.validate(&serde_json::json!({
+                "schema": "alert.v3",
+                "id": "inc-1",
+                "title": "cpu high",
+                "severity": "high",
+                "status": "firing",
+                "tags": [],
+                "source": "generic",
+                "occurred_at": occurred_at,
+                "received_at": "2026-03-01T12:00:00Z"
+            }))
+        };
+        assert!(epoch(serde_json::json!(1_772_366_400)).is_ok());
+        assert!(epoch(serde_json::json!("1772366400000")).is_ok());
+        assert!(epoch(serde_json::json!(true)).is_err());
+
+        let errors = registry
+            .validate(&serde_json::json!({
+                "schema": "alert.v1",
+                "id": "inc-2",
+                "title": "cpu high",
+                "severity": "high",
+                "tags": [],
+                "source": "generic",
+                "occurred_at": "2999-01-01T00:00:00Z"
+            }))
+            .expect_err("future alert")
+            .errors;
+        assert_eq!(errors[0].pointer, "/occurred_at");
+        assert_eq!(errors[0].code, "future_timestamp");
+        assert!(registry
+            .validate(&serde_json::json!({
+                "schema": "alert.v3",
+                "id": "inc-3",
+                "title": "cpu high",
+                "severity": "high",
+                "status": "firing",
+                "tags": [],
+                "source": "generic",
+                "occurred_at": "last tuesday",
+                "received_at": "2026-03-01T12:00:00Z"
+            }))
+            .is_err());
+    }
+
+    #[test]
+    fn validates_infrastructure_facts() {
+        let registry = canonical_registry();
+        let pod = serde_json::json!({
+            "schema": "pod.v1",
+            "name": "checkout-7d9f",
+            "namespace": "shop",
+            "service": "checkout",
+            "phase": "running",
+            "restarts": 4,
+            "ready": false,
+            "reason": "CrashLoopBackOff",
+            "observed_at": "1"
+        });
+        assert_eq!(
+            registry.validate(&pod).map(|v| v.value),
+            Ok(SchemaId::new("pod", 1))
+        );
+
+        let violations = registry
+            .validate(&serde_json::json!({
+                "schema": "deploy.v1",
+                "name": "checkout",
+                "namespace": "shop",
+                "service": "checkout",
+                "version": "v42",
+                "replicas": -1,
+                "available_replicas": 2.5,
+                "observed_at": "1"
+            }))
+            .expect_err("invalid deploy");
+        let mut pointers: Vec<&str> = violations
+            .errors
+            .iter()
+            .map(|v| v.pointer.as_str())
+            .collect();
+        pointers.sort_unstable();
+        assert_eq!(pointers, vec!["/available_replicas", "/replicas"]);
+
+        let deploy = |available: i64, observed_at: &str| {
+            serde_json::json!({
+                "schema": "deploy.v1",
+                "name": "checkout",
+                "namespace": "shop",
+                "service": "checkout",
+                "version": "v42",
+                "replicas": 3,
+                "available_replicas": available,
+                "observed_at": observed_at
+            })
+        };
+        let errors = registry
+            .validate(&deploy(5, "1"))
+            .expect_err("more available than desired")
+            .errors;
+        assert_eq!(errors[0].pointer, "/available_replicas");
+        assert_eq!(errors[0].code, "exceeds_replicas");
+        for observed_at in ["last tuesday", "2999-01-01T00:00:00Z"] {
+            let errors = registry
+                .validate(&deploy(3, observed_at))
+                .expect_err("bad observed_at")
+                .errors;
+            assert_eq!(errors[0].pointer, "/observed_at");
+        }
+
+        let metric = CanonicalMetricV1 {
+            schema: "metric.v1".into(),
+            service: "checkout".into(),
+            name: "error_rate".into(),
+            value: f64::NAN,
+            unit: None,
+            threshold: Some(f64::INFINITY),
+            observed_at: Timestamp::now(),
+        };
+        let pointers: Vec<String> = infra::metric_violations(&metric)
+            .into_iter()
+            .map(|v| v.pointer)
+            .collect();
+        assert_eq!(pointers, vec!["/value", "/threshold"]);
+
+        assert!(registry
+            .validate(&serde_json::json!({
+                "schema": "change.v1",
+                "id": "chg-1",
+                "service": "checkout",
+                "kind": "migration",
+                "description": "schema migration",
+                "occurred_at": "1"
+            }))
+            .is_err());
     }
 }
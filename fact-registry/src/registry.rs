@@ -0,0 +1,289 @@
+use crate::json_schema::{self, Violation};
+use crate::report::{Validated, ValidationReport};
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Checks beyond what a schema's JSON Schema document can express, run on
+/// payloads the document accepts. May warn without rejecting.
+pub type Validator = fn(&Value) -> ValidationReport;
+
+/// Rewrites a payload of one version into the next version of its kind.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// A schema name such as `alert.v2`, split into its kind and version.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaId {
+    pub kind: String,
+    pub version: u32,
+}
+
+impl SchemaId {
+    pub fn new(kind: impl Into<String>, version: u32) -> Self {
+        Self {
+            kind: kind.into(),
+            version,
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let (kind, version) = name
+            .rsplit_once(".v")
+            .ok_or_else(|| format!("schema '{name}' is not of the form <kind>.v<version>"))?;
+        let version = version
+            .parse()
+            .map_err(|_| format!("schema '{name}' has an invalid version"))?;
+        if kind.is_empty() {
+            return Err(format!("schema '{name}' has no kind"));
+        }
+        Ok(Self::new(kind, version))
+    }
+}
+
+impl std::fmt::Display for SchemaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.v{}", self.kind, self.version)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    document: Value,
+    check: Validator,
+}
+
+/// Schema versions per fact kind, each with its generated JSON Schema
+/// document and extra checks, and the migrations that step a payload from
+/// one version to the next.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+    entries: BTreeMap<SchemaId, Entry>,
+    /// Keyed by the version migrated from.
+    migrations: BTreeMap<SchemaId, Migration>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `T` as `kind` version `version`, with its JSON Schema
+    /// document generated from the type.
+    pub fn schema<T: JsonSchema>(mut self, kind: &str, version: u32, check: Validator) -> Self {
+        let id = SchemaId::new(kind, version);
+        let mut document = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default();
+        if let Some(fields) = document.as_object_mut() {
+            fields.insert("$id".into(), Value::String(id.to_string()));
+        }
+        self.entries.insert(id, Entry { document, check });
+        self
+    }
+
+    /// Register the migration from `kind` version `from` to `from + 1`.
+    pub fn migration(mut self, kind: &str, from: u32, migrate: Migration) -> Self {
+        self.migrations.insert(SchemaId::new(kind, from), migrate);
+        self
+    }
+
+    /// Every registered schema, by kind then version.
+    pub fn schemas(&self) -> impl Iterator<Item = &SchemaId> {
+        self.entries.keys()
+    }
+
+    /// The JSON Schema document for `id`.
+    pub fn document(&self, id: &SchemaId) -> Option<&Value> {
+        self.entries.get(id).map(|entry| &entry.document)
+    }
+
+    pub fn latest(&self, kind: &str) -> Option<SchemaId> {
+        self.entries
+            .keys()
+            .filter(|id| id.kind == kind)
+            .max()
+            .cloned()
+    }
+
+    /// Validate `payload` against the schema its `schema` field declares,
+    /// collecting every violation.
+    pub fn validate(&self, payload: &Value) -> Result<Validated<SchemaId>, ValidationReport> {
+        let declared = payload
+            .get("schema")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Violation::new("/schema", "required", "is required"))?;
+        let id = SchemaId::parse(declared)
+            .map_err(|err| Violation::new("/schema", "invalid_schema", err))?;
+        let entry = self.entries.get(&id).ok_or_else(|| {
+            Violation::new(
+                "/schema",
+                "unsupported_schema",
+                format!("unsupported schema '{id}'"),
+            )
+        })?;
+        let violations = json_schema::validate(&entry.document, payload);
+        if !violations.is_empty() {
+            return Err(ValidationReport::rejecting(violations));
+        }
+        (entry.check)(payload).into_result(id)
+    }
+
+    /// Validate `payload` against its declared schema, then migrate it one
+    /// version at a time to the latest version of its kind, validating each
+    /// step's output.
+    pub fn upgrade(&self, payload: Value) -> Result<Validated<Value>, ValidationReport> {
+        let id = self.validate(&payload)?.value;
+        let latest = self.latest(&id.kind).ok_or_else(|| {
+            Violation::new(
+                "/schema",
+                "unsupported_schema",
+                format!("unsupported schema '{id}'"),
+            )
+        })?;
+        self.upgrade_to(payload, &latest)
+    }
+
+    /// Migrate `payload` up to `target`, which must be the same kind and no
+    /// older than the payload's declared version. Warnings from every step
+    /// are kept.
+    pub fn upgrade_to(
+        &self,
+        mut payload: Value,
+        target: &SchemaId,
+    ) -> Result<Validated<Value>, ValidationReport> {
+        let Validated {
+            value: mut id,
+            mut warnings,
+        } = self.validate(&payload)?;
+        if id.kind != target.kind || id.version > target.version {
+            return Err(Violation::new(
+                "/schema",
+                "unsupported_migration",
+                format!("cannot migrate {id} to {target}"),
+            )
+            .into());
+        }
+        while id.version < target.version {
+            let migration_failed =
+                |message: String| Violation::new("", "migration_failed", message);
+            let migrate = self
+                .migrations
+                .get(&id)
+                .ok_or_else(|| migration_failed(format!("no migration from {id}")))?;
+            payload = migrate(payload).map_err(migration_failed)?;
+            let next = self.validate(&payload)?;
+            if next.value != SchemaId::new(&id.kind, id.version + 1) {
+                return Err(migration_failed(format!(
+                    "migration from {id} produced {}",
+                    next.value
+                ))
+                .into());
+            }
+            warnings.extend(next.warnings);
+            id = next.value;
+        }
+        Ok(Validated {
+            value: payload,
+            warnings,
+        })
+    }
+}
+
+/// Violations as one message, for callers that only report a string.
+pub fn describe(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Widget {
+        schema: String,
+        name: String,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct WidgetV3 {
+        schema: String,
+        names: Vec<String>,
+    }
+
+    fn no_checks(_: &Value) -> ValidationReport {
+        ValidationReport::new()
+    }
+
+    fn v1_to_v2(mut payload: Value) -> Result<Value, String> {
+        payload["schema"] = json!("widget.v2");
+        Ok(payload)
+    }
+
+    fn v2_to_v3(mut payload: Value) -> Result<Value, String> {
+        let name = payload
+            .as_object_mut()
+            .and_then(|fields| fields.remove("name"));
+        payload["names"] = json!([name]);
+        payload["schema"] = json!("widget.v3");
+        Ok(payload)
+    }
+
+    fn widgets() -> SchemaRegistry {
+        SchemaRegistry::new()
+            .schema::<Widget>("widget", 1, no_checks)
+            .schema::<Widget>("widget", 2, no_checks)
+            .schema::<WidgetV3>("widget", 3, no_checks)
+            .migration("widget", 1, v1_to_v2)
+            .migration("widget", 2, v2_to_v3)
+    }
+
+    #[test]
+    fn validates_declared_version_and_chains_migrations() {
+        let registry = widgets();
+        let v1 = json!({"schema": "widget.v1", "name": "a"});
+        assert_eq!(
+            registry.validate(&v1).map(|v| v.value),
+            Ok(SchemaId::new("widget", 1))
+        );
+        assert_eq!(
+            registry.validate(&json!({"schema": "widget.v3", "name": "a"})),
+            Err(Violation::new("/names", "required", "is required").into())
+        );
+        assert_eq!(
+            registry.validate(&json!({"schema": "widget.v9"})),
+            Err(Violation::new(
+                "/schema",
+                "unsupported_schema",
+                "unsupported schema 'widget.v9'"
+            )
+            .into())
+        );
+        assert_eq!(
+            registry
+                .document(&SchemaId::new("widget", 3))
+                .map(|d| &d["$id"]),
+            Some(&json!("widget.v3"))
+        );
+
+        assert_eq!(
+            registry.upgrade(v1.clone()).map(|v| v.value),
+            Ok(json!({"schema": "widget.v3", "names": ["a"]}))
+        );
+        assert_eq!(
+            registry
+                .upgrade_to(v1, &SchemaId::new("widget", 2))
+                .map(|v| v.value),
+            Ok(json!({"schema": "widget.v2", "name": "a"}))
+        );
+        assert!(registry
+            .upgrade_to(
+                json!({"schema": "widget.v2", "name": "a"}),
+                &SchemaId::new("widget", 1)
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::json_schema::Violation;
+use serde::{Deserialize, Serialize};
+
+/// Every problem found in a payload: errors that reject it, and warnings
+/// about soft issues, such as a value coerced to a default, that do not.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub errors: Vec<Violation>,
+    pub warnings: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A report of `errors` alone.
+    pub fn rejecting(errors: Vec<Violation>) -> Self {
+        Self {
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn error(mut self, violation: Violation) -> Self {
+        self.errors.push(violation);
+        self
+    }
+
+    pub fn warn(mut self, violation: Violation) -> Self {
+        self.warnings.push(violation);
+        self
+    }
+
+    /// Append `other`'s errors and warnings.
+    pub fn merge(&mut self, other: ValidationReport) {
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `value` with this report's warnings when there are no errors.
+    pub fn into_result<T>(self, value: T) -> Result<Validated<T>, ValidationReport> {
+        if self.is_valid() {
+            Ok(Validated {
+                value,
+                warnings: self.warnings,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<Violation> for ValidationReport {
+    fn from(violation: Violation) -> Self {
+        Self::rejecting(vec![violation])
+    }
+}
+
+/// The errors as one message, for callers that only report a string.
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", crate::describe(&self.errors))
+    }
+}
+
+/// A value that passed validation, with any warnings raised on the way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Validated<T> {
+    pub value: T,
+    pub warnings: Vec<Violation>,
+}
+
+impl<T> Validated<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Validated<U> {
+        Validated {
+            value: f(self.value),
+            warnings: self.warnings,
+        }
+    }
+}
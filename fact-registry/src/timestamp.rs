@@ -0,0 +1,150 @@
+//! Event times as sources send them — RFC 3339 strings, or epoch seconds or
+//! milliseconds as numbers or numeric strings — normalized to UTC.
+
+use crate::json_schema::Violation;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// How far ahead of the local clock an event time may be before it is
+/// rejected rather than put down to clock skew.
+pub const MAX_FUTURE_SKEW: Duration = Duration::minutes(15);
+
+/// Epoch values at or above this are read as milliseconds; as seconds they
+/// would be more than a thousand years out.
+const EPOCH_MILLIS_FROM: i64 = 100_000_000_000;
+
+/// A UTC instant, written as RFC 3339 and read from any format
+/// [`parse_timestamp`] accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Self(Utc::now())
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        parse_timestamp(&raw)
+            .map(Timestamp)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for Timestamp {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Timestamp".into()
+    }
+
+    /// Everything [`parse_timestamp`] reads: an RFC 3339 string, or epoch
+    /// seconds or milliseconds as an integer or numeric string.
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                {"type": "string", "format": "date-time"},
+                {"type": "string", "pattern": "^\\s*-?[0-9]+\\s*$"},
+                {"type": "integer"}
+            ]
+        })
+    }
+}
+
+/// `raw` as a UTC instant: an RFC 3339 string, or epoch seconds or
+/// milliseconds as a number or numeric string.
+pub fn parse_timestamp(raw: &Value) -> Result<DateTime<Utc>, String> {
+    let epoch = match raw {
+        Value::String(text) => {
+            let text = text.trim();
+            match text.parse::<i64>() {
+                Ok(epoch) => epoch,
+                Err(_) => {
+                    return DateTime::parse_from_rfc3339(text)
+                        .map(|time| time.with_timezone(&Utc))
+                        .map_err(|err| format!("'{text}' is not an RFC 3339 timestamp: {err}"));
+                }
+            }
+        }
+        Value::Number(number) => number
+            .as_i64()
+            .ok_or_else(|| format!("{number} is not a whole epoch timestamp"))?,
+        other => return Err(format!("{other} is not a timestamp")),
+    };
+    let time = if epoch.abs() >= EPOCH_MILLIS_FROM {
+        DateTime::from_timestamp_millis(epoch)
+    } else {
+        DateTime::from_timestamp(epoch, 0)
+    };
+    time.ok_or_else(|| format!("epoch timestamp {epoch} is out of range"))
+}
+
+/// The timestamp at `pointer` in `payload`, rejected if it does not parse or
+/// lies more than [`MAX_FUTURE_SKEW`] after `now`.
+pub fn check_timestamp(
+    payload: &Value,
+    pointer: &str,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, Violation> {
+    let raw = payload.pointer(pointer).unwrap_or(&Value::Null);
+    let time =
+        parse_timestamp(raw).map_err(|err| Violation::new(pointer, "invalid_timestamp", err))?;
+    if time > now + MAX_FUTURE_SKEW {
+        return Err(Violation::new(
+            pointer,
+            "future_timestamp",
+            format!("{time} is more than {MAX_FUTURE_SKEW} ahead of {now}"),
+        ));
+    }
+    Ok(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_rfc3339_and_epoch_seconds_or_millis() {
+        let fired = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for raw in [
+            json!("2026-03-01T13:00:00+01:00"),
+            json!(fired.timestamp()),
+            json!(fired.timestamp().to_string()),
+            json!(fired.timestamp_millis()),
+        ] {
+            assert_eq!(parse_timestamp(&raw), Ok(fired), "{raw}");
+        }
+        assert!(parse_timestamp(&json!("yesterday")).is_err());
+        assert!(parse_timestamp(&json!(1.5)).is_err());
+
+        let payload = json!({"at": "2026-03-01T12:30:00Z"});
+        assert!(check_timestamp(&payload, "/at", fired + Duration::minutes(20)).is_ok());
+        assert_eq!(
+            check_timestamp(&payload, "/at", fired).map_err(|v| v.code),
+            Err("future_timestamp".to_string())
+        );
+
+        let written = serde_json::to_value(Timestamp(fired)).unwrap();
+        assert_eq!(written, json!("2026-03-01T12:00:00Z"));
+        assert_eq!(
+            serde_json::from_value(json!(fired.timestamp())).ok(),
+            Some(Timestamp(fired))
+        );
+    }
+}
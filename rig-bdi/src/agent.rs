@@ -0,0 +1,685 @@
+use crate::beliefs::BeliefBase;
+use crate::commitment::Commitment;
+use crate::conflict::{self, Conflict};
+use crate::desire::Desire;
+use crate::environment::{Environment, Outcome, Percepts};
+use crate::intention::Intention;
+use crate::planner::Planner;
+use rig_planner::ActionSchema;
+use rig_rete::{ReteNetwork, Rule};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// Why [`Agent::adopt`] turned a desire down.
+#[derive(Clone, Debug)]
+pub enum Rejection<E> {
+    /// The planner found no plan.
+    Unplanned(E),
+    /// The plan has opposing effects to the running intention `with`, which
+    /// was dropped as well.
+    Conflicting {
+        with: Box<Intention>,
+        conflicts: Vec<Conflict>,
+    },
+}
+
+/// A BDI agent: beliefs revised from facts, desires fired by the rules of a
+/// [`ReteNetwork`], and intentions planned by a [`Planner`] and reconsidered
+/// under a [`Commitment`] strategy.
+pub struct Agent<B, N, R, P> {
+    beliefs: B,
+    network: N,
+    planner: P,
+    commitment: Commitment,
+    /// The latest desire per scope, kept until an intention for it ends.
+    desires: Vec<Desire>,
+    intentions: Vec<Intention>,
+    /// Steps already carried out per scope by intentions dropped on
+    /// reconsideration, which a readopted desire plans on from.
+    carried: BTreeMap<String, Vec<ActionSchema>>,
+    _rule: PhantomData<fn() -> R>,
+}
+
+impl<B, N, R, P> Agent<B, N, R, P>
+where
+    B: BeliefBase,
+    N: ReteNetwork<B::Fact, R>,
+    R: Rule<Fact = B::Fact, Action = Desire>,
+    P: Planner<B>,
+{
+    pub fn new(beliefs: B, network: N, planner: P) -> Self {
+        Self {
+            beliefs,
+            network,
+            planner,
+            commitment: Commitment::default(),
+            desires: Vec::new(),
+            intentions: Vec::new(),
+            carried: BTreeMap::new(),
+            _rule: PhantomData,
+        }
+    }
+
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn beliefs(&self) -> &B {
+        &self.beliefs
+    }
+
+    pub fn desires(&self) -> &[Desire] {
+        &self.desires
+    }
+
+    pub fn intentions(&self) -> &[Intention] {
+        &self.intentions
+    }
+
+    pub fn intention(&self, scope: &str) -> Option<&Intention> {
+        self.intentions.iter().find(|i| i.desire.scope == scope)
+    }
+
+    /// Revise beliefs with `fact` and record the desires of rules it fired,
+    /// each replacing any earlier desire for its scope.
+    pub fn perceive(&mut self, fact: &B::Fact) -> Vec<Desire> {
+        self.beliefs.revise(fact);
+        let fired: Vec<Desire> = self
+            .network
+            .on_assert(fact)
+            .iter()
+            .flat_map(|m| m.rule.actions(&m.bindings))
+            .collect();
+        for desire in &fired {
+            self.desires.retain(|d| d.scope != desire.scope);
+            self.desires.push(desire.clone());
+        }
+        fired
+    }
+
+    /// The most pressing desire not already intended, by priority and then
+    /// deadline; the earliest raised wins ties.
+    pub fn deliberate(&self) -> Option<Desire> {
+        self.desires
+            .iter()
+            .filter(|d| self.intention(&d.scope).is_none())
+            .cloned()
+            .reduce(|best, d| if d.outranks(&best) { d } else { best })
+    }
+
+    /// Commit to a plan for `desire` under the current beliefs, on from any
+    /// steps a dropped intention for its scope carried out. A desire with no
+    /// plan is discarded; one whose plan conflicts with a running intention
+    /// is discarded along with that intention.
+    pub fn adopt(&mut self, desire: Desire) -> Result<&Intention, Rejection<P::Error>> {
+        let done = self.carried.remove(&desire.scope).unwrap_or_default();
+        let plan = match self.planner.plan(&self.beliefs, &desire, &done) {
+            Ok(plan) => plan,
+            Err(err) => {
+                self.desires.retain(|d| d != &desire);
+                return Err(Rejection::Unplanned(err));
+            }
+        };
+        let intention = Intention::new(desire, plan);
+        let conflicting = self.intentions.iter().find_map(|running| {
+            let found = conflict::conflicts(running, &intention);
+            (!found.is_empty()).then(|| (running.desire.scope.clone(), found))
+        });
+        if let Some((scope, conflicts)) = conflicting
+            && let Some(with) = self.finish(&scope)
+        {
+            self.desires.retain(|d| d != &intention.desire);
+            return Err(Rejection::Conflicting {
+                with: Box::new(with),
+                conflicts,
+            });
+        }
+        self.intentions.push(intention);
+        Ok(&self.intentions[self.intentions.len() - 1])
+    }
+
+    /// Why the intention for `scope` should be dropped, under the agent's
+    /// commitment strategy.
+    pub fn reconsider(&self, scope: &str) -> Option<String> {
+        let intention = self.intention(scope)?;
+        self.commitment
+            .reconsider(intention, &self.beliefs, &self.desires)
+    }
+
+    /// Remove the intention for `scope`, and its desire unless that has
+    /// since changed.
+    pub fn finish(&mut self, scope: &str) -> Option<Intention> {
+        let pos = self
+            .intentions
+            .iter()
+            .position(|i| i.desire.scope == scope)?;
+        let intention = self.intentions.remove(pos);
+        self.desires.retain(|d| d != &intention.desire);
+        Some(intention)
+    }
+
+    /// The intention to step next: the most pressing one by priority and
+    /// then deadline, earliest adopted on ties, except that `current` keeps
+    /// running unless another intention outranks it.
+    pub fn schedule(&self, current: Option<&str>) -> Option<&Intention> {
+        let best = self.intentions.iter().reduce(|best, i| {
+            if i.desire.outranks(&best.desire) {
+                i
+            } else {
+                best
+            }
+        })?;
+        match current.and_then(|scope| self.intention(scope)) {
+            Some(running) if !best.desire.outranks(&running.desire) => Some(running),
+            _ => Some(best),
+        }
+    }
+
+    /// Perceive → deliberate → plan → act until `percepts` runs dry and no
+    /// intention is left.
+    ///
+    /// Intentions, one per scope, advance one step at a time, so a step is
+    /// never interrupted. Between steps, facts that have already arrived are
+    /// perceived, every intention is reconsidered, new desires are adopted,
+    /// and the next step goes to the [`schedule`](Self::schedule)d intention,
+    /// preempting the one that ran last if it is outranked.
+    pub fn run<S, E>(&mut self, percepts: &mut S, env: &mut E)
+    where
+        S: Percepts<B::Fact>,
+        E: Environment<B, P::Error>,
+    {
+        let mut running: Option<String> = None;
+        loop {
+            if self.intentions.is_empty() {
+                let Some(fact) = percepts.next() else {
+                    return;
+                };
+                self.sense(&fact, env);
+            }
+            while let Some(fact) = percepts.poll() {
+                self.sense(&fact, env);
+            }
+            self.reconsider_all(env);
+            self.adopt_all(env);
+
+            let Some(next) = self.schedule(running.as_deref()) else {
+                continue;
+            };
+            if let Some(current) = running.as_deref().and_then(|scope| self.intention(scope))
+                && current.desire.scope != next.desire.scope
+            {
+                env.preempted(current, next);
+            }
+            let scope = next.desire.scope.clone();
+            self.step(&scope, env);
+            running = Some(scope);
+        }
+    }
+
+    fn sense<E: Environment<B, P::Error>>(&mut self, fact: &B::Fact, env: &mut E) {
+        env.perceived(fact);
+        self.perceive(fact);
+    }
+
+    fn reconsider_all<E: Environment<B, P::Error>>(&mut self, env: &mut E) {
+        let dropped: Vec<(String, String)> = self
+            .intentions
+            .iter()
+            .filter_map(|i| {
+                let scope = &i.desire.scope;
+                self.reconsider(scope).map(|reason| (scope.clone(), reason))
+            })
+            .collect();
+        for (scope, reason) in dropped {
+            if let Some(intention) = self.finish(&scope) {
+                let carried = self.carried.entry(scope).or_default();
+                carried.extend(
+                    intention
+                        .completed()
+                        .into_iter()
+                        .map(|i| intention.plan.steps[i].clone()),
+                );
+                env.ended(&intention, &Outcome::Dropped(reason));
+            }
+        }
+    }
+
+    fn adopt_all<E: Environment<B, P::Error>>(&mut self, env: &mut E) {
+        while let Some(desire) = self.deliberate() {
+            match self.adopt(desire.clone()) {
+                Ok(intention) => env.adopted(intention),
+                Err(Rejection::Unplanned(err)) => env.unplanned(&desire, err),
+                Err(Rejection::Conflicting { with, conflicts }) => {
+                    let reason = format!("conflicts with {}", desire.scope);
+                    env.ended(&with, &Outcome::Dropped(reason));
+                    env.conflicted(&desire, &with, &conflicts);
+                }
+            }
+        }
+    }
+
+    fn step<E: Environment<B, P::Error>>(&mut self, scope: &str, env: &mut E) {
+        let Some(intention) = self.intentions.iter_mut().find(|i| i.desire.scope == scope) else {
+            return;
+        };
+        if let Some(outcome) = env.step(&self.beliefs, intention)
+            && let Some(intention) = self.finish(scope)
+        {
+            env.ended(&intention, &outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::LinearNetwork;
+    use crate::planner::StripsPlanner;
+    use chrono::{DateTime, Utc};
+    use rig_effects::Effect;
+    use rig_planner::{ActionSchema, Unreachable, WorldState};
+    use rig_rete::{Condition, Fact};
+    use std::collections::VecDeque;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Alert {
+        id: String,
+        signal: String,
+        severity: i32,
+    }
+
+    impl Fact for Alert {
+        type Id = String;
+
+        fn id(&self) -> &String {
+            &self.id
+        }
+
+        fn timestamp(&self) -> DateTime<Utc> {
+            DateTime::UNIX_EPOCH
+        }
+    }
+
+    fn alert(id: &str, signal: &str, severity: i32) -> Alert {
+        Alert {
+            id: id.into(),
+            signal: signal.into(),
+            severity,
+        }
+    }
+
+    #[derive(Default)]
+    struct Signals(Vec<Alert>);
+
+    impl BeliefBase for Signals {
+        type Fact = Alert;
+
+        fn revise(&mut self, fact: &Alert) {
+            self.0.retain(|a| a.id != fact.id);
+            self.0.push(fact.clone());
+        }
+
+        fn world_state(&self, scope: &str) -> WorldState {
+            self.0
+                .iter()
+                .filter(|a| a.id == scope)
+                .map(|a| a.signal.clone())
+                .collect()
+        }
+    }
+
+    struct SignalIn(&'static [&'static str]);
+
+    impl Condition<Alert> for SignalIn {
+        type Bindings = Option<Alert>;
+
+        fn matches(&self, fact: &Alert) -> Option<Option<Alert>> {
+            self.0
+                .contains(&fact.signal.as_str())
+                .then(|| Some(fact.clone()))
+        }
+
+        fn description(&self) -> &str {
+            "signal matches"
+        }
+    }
+
+    struct Remediate {
+        signals: &'static [&'static str],
+        conditions: Vec<Box<dyn Condition<Alert, Bindings = Option<Alert>>>>,
+    }
+
+    impl Remediate {
+        fn new(signals: &'static [&'static str]) -> Self {
+            Self {
+                signals,
+                conditions: vec![Box::new(SignalIn(signals))],
+            }
+        }
+    }
+
+    impl Clone for Remediate {
+        fn clone(&self) -> Self {
+            Self::new(self.signals)
+        }
+    }
+
+    impl Rule for Remediate {
+        type Fact = Alert;
+        type Action = Desire;
+        type Bindings = Option<Alert>;
+
+        fn id(&self) -> &str {
+            self.signals[0]
+        }
+
+        fn conditions(&self) -> &[Box<dyn Condition<Alert, Bindings = Option<Alert>>>] {
+            &self.conditions
+        }
+
+        fn actions(&self, bindings: &Option<Alert>) -> Vec<Desire> {
+            bindings
+                .iter()
+                .map(|a| {
+                    Desire::new(a.id.clone(), vec!["recovered".into()]).with_priority(a.severity)
+                })
+                .collect()
+        }
+
+        fn priority(&self) -> i32 {
+            0
+        }
+
+        fn description(&self) -> &str {
+            "remediate a detected signal"
+        }
+    }
+
+    type TestAgent = Agent<Signals, LinearNetwork<Remediate>, Remediate, StripsPlanner>;
+
+    fn agent(signals: &'static [&'static str]) -> TestAgent {
+        let actions = vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop")
+                .adds("has_pod_logs"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("crashloop")
+                .requires("has_pod_logs")
+                .adds("recovered"),
+            ActionSchema::new("scale-up", Effect::Mutate)
+                .requires("overload")
+                .adds("capacity_raised")
+                .adds("recovered"),
+            ActionSchema::new("scale-down", Effect::Mutate)
+                .requires("idle")
+                .deletes("capacity_raised")
+                .adds("recovered"),
+        ];
+        Agent::new(
+            Signals::default(),
+            LinearNetwork::new(vec![Remediate::new(signals)]),
+            StripsPlanner::new(actions),
+        )
+    }
+
+    /// Facts to wait for, then facts found by each poll round between
+    /// steps, where `None` ends a round.
+    #[derive(Default)]
+    struct Script {
+        queued: VecDeque<Alert>,
+        between_steps: VecDeque<Option<Alert>>,
+    }
+
+    impl Percepts<Alert> for Script {
+        fn next(&mut self) -> Option<Alert> {
+            self.queued.pop_front()
+        }
+
+        fn poll(&mut self) -> Option<Alert> {
+            self.between_steps.pop_front().flatten()
+        }
+    }
+
+    /// Runs one ready step per call and journals every decision.
+    #[derive(Default)]
+    struct Recorder {
+        ran: Vec<String>,
+        journal: Vec<String>,
+    }
+
+    impl Environment<Signals, Unreachable> for Recorder {
+        fn step(&mut self, _beliefs: &Signals, intention: &mut Intention) -> Option<Outcome> {
+            let Some(&next) = intention.ready().first() else {
+                return Some(Outcome::Achieved);
+            };
+            self.ran.push(format!(
+                "{}/{}",
+                intention.desire.scope, intention.plan.steps[next].name
+            ));
+            intention.complete(next);
+            intention.is_achieved().then_some(Outcome::Achieved)
+        }
+
+        fn adopted(&mut self, intention: &Intention) {
+            self.journal
+                .push(format!("adopted {}", intention.desire.scope));
+        }
+
+        fn preempted(&mut self, intention: &Intention, by: &Intention) {
+            self.journal.push(format!(
+                "preempted {} for {}",
+                intention.desire.scope, by.desire.scope
+            ));
+        }
+
+        fn ended(&mut self, intention: &Intention, outcome: &Outcome) {
+            self.journal
+                .push(format!("ended {}: {outcome:?}", intention.desire.scope));
+        }
+
+        fn unplanned(&mut self, desire: &Desire, _error: Unreachable) {
+            self.journal.push(format!("unplanned {}", desire.scope));
+        }
+
+        fn conflicted(&mut self, desire: &Desire, with: &Intention, conflicts: &[Conflict]) {
+            self.journal.push(format!(
+                "conflicted {} with {} over {}",
+                desire.scope, with.desire.scope, conflicts[0].proposition
+            ));
+        }
+    }
+
+    fn run(
+        mut agent: TestAgent,
+        queued: Vec<Alert>,
+        between_steps: Vec<Option<Alert>>,
+    ) -> Recorder {
+        let mut script = Script {
+            queued: queued.into(),
+            between_steps: between_steps.into(),
+        };
+        let mut recorder = Recorder::default();
+        agent.run(&mut script, &mut recorder);
+        assert!(agent.intentions().is_empty());
+        recorder
+    }
+
+    #[test]
+    fn unplannable_desire_is_reported_while_another_runs() {
+        let recorder = run(
+            agent(&["crashloop", "disk-full"]),
+            vec![alert("inc-1", "crashloop", 3)],
+            vec![None, Some(alert("inc-2", "disk-full", 1)), None],
+        );
+        assert_eq!(
+            recorder.ran,
+            vec!["inc-1/inspect-pod-logs", "inc-1/rollback-deployment"]
+        );
+        assert_eq!(
+            recorder.journal,
+            vec!["adopted inc-1", "unplanned inc-2", "ended inc-1: Achieved"]
+        );
+    }
+
+    #[test]
+    fn higher_priority_intention_preempts_between_steps() {
+        let recorder = run(
+            agent(&["crashloop"]),
+            vec![alert("low", "crashloop", 1)],
+            vec![None, Some(alert("high", "crashloop", 4)), None],
+        );
+        assert_eq!(
+            recorder.ran,
+            vec![
+                "low/inspect-pod-logs",
+                "high/inspect-pod-logs",
+                "high/rollback-deployment",
+                "low/rollback-deployment",
+            ]
+        );
+        assert_eq!(
+            recorder.journal,
+            vec![
+                "adopted low",
+                "adopted high",
+                "preempted low for high",
+                "ended high: Achieved",
+                "ended low: Achieved",
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_desires_are_rejected_together() {
+        let recorder = run(
+            agent(&["overload", "idle"]),
+            vec![alert("recover", "overload", 3)],
+            vec![Some(alert("save-cost", "idle", 1)), None],
+        );
+        assert!(recorder.ran.is_empty());
+        assert_eq!(
+            recorder.journal,
+            vec![
+                "adopted recover",
+                "ended recover: Dropped(\"conflicts with save-cost\")",
+                "conflicted save-cost with recover over capacity_raised",
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_priority_does_not_preempt() {
+        let mut agent = agent(&["crashloop"]);
+        agent.perceive(&alert("first", "crashloop", 2));
+        agent.perceive(&alert("second", "crashloop", 2));
+        agent.adopt(agent.deliberate().unwrap()).unwrap();
+        agent.adopt(agent.deliberate().unwrap()).unwrap();
+
+        let scope = |i: Option<&Intention>| i.map(|i| i.desire.scope.clone());
+        assert_eq!(scope(agent.schedule(None)), Some("first".to_string()));
+        assert_eq!(
+            scope(agent.schedule(Some("second"))),
+            Some("second".to_string())
+        );
+    }
+
+    #[test]
+    fn deadline_breaks_priority_ties() {
+        let mut agent = agent(&["crashloop"]);
+        agent.perceive(&alert("later", "crashloop", 2));
+        agent.perceive(&alert("sooner", "crashloop", 2));
+        let due = |secs| Some(DateTime::UNIX_EPOCH + chrono::Duration::seconds(secs));
+        agent.desires[0].deadline = due(600);
+        agent.desires[1].deadline = due(60);
+        agent.adopt(agent.deliberate().unwrap()).unwrap();
+        assert_eq!(agent.intentions()[0].desire.scope, "sooner");
+
+        agent.adopt(agent.deliberate().unwrap()).unwrap();
+        let scope = |i: Option<&Intention>| i.map(|i| i.desire.scope.clone());
+        assert_eq!(
+            scope(agent.schedule(Some("later"))),
+            Some("sooner".to_string())
+        );
+    }
+
+    #[test]
+    fn deliberate_prefers_priority_then_order() {
+        let mut agent = agent(&["crashloop"]);
+        agent.perceive(&alert("low", "crashloop", 1));
+        agent.perceive(&alert("first", "crashloop", 4));
+        agent.perceive(&alert("second", "crashloop", 4));
+        assert_eq!(
+            agent.deliberate().map(|d| d.scope),
+            Some("first".to_string())
+        );
+
+        agent.adopt(agent.deliberate().unwrap()).unwrap();
+        assert_eq!(
+            agent.deliberate().map(|d| d.scope),
+            Some("second".to_string())
+        );
+    }
+
+    #[test]
+    fn blind_commitment_ignores_new_facts() {
+        let recorder = run(
+            agent(&["crashloop", "healthy"]),
+            vec![alert("inc-1", "crashloop", 3)],
+            vec![None, Some(alert("inc-1", "healthy", 3)), None],
+        );
+        assert_eq!(recorder.ran.len(), 2);
+        assert_eq!(recorder.journal[1], "ended inc-1: Achieved");
+    }
+
+    #[test]
+    fn single_minded_drops_an_impossible_intention() {
+        let recorder = run(
+            agent(&["crashloop", "healthy"]).with_commitment(Commitment::SingleMinded),
+            vec![alert("inc-1", "crashloop", 3)],
+            vec![None, Some(alert("inc-1", "healthy", 3)), None],
+        );
+        assert_eq!(recorder.ran, vec!["inc-1/inspect-pod-logs"]);
+        assert_eq!(
+            recorder.journal,
+            vec![
+                "adopted inc-1",
+                "ended inc-1: Dropped(\"steps no longer possible: rollback-deployment\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn open_minded_readopts_a_changed_desire() {
+        let recorder = run(
+            agent(&["crashloop"]).with_commitment(Commitment::OpenMinded),
+            vec![alert("inc-1", "crashloop", 2)],
+            vec![None, Some(alert("inc-1", "crashloop", 4)), None],
+        );
+        assert_eq!(
+            recorder.journal,
+            vec![
+                "adopted inc-1",
+                "ended inc-1: Dropped(\"desire changed\")",
+                "adopted inc-1",
+                "ended inc-1: Achieved",
+            ]
+        );
+        assert_eq!(
+            recorder.ran,
+            vec!["inc-1/inspect-pod-logs", "inc-1/rollback-deployment"]
+        );
+    }
+
+    #[test]
+    fn reasserted_fact_replaces_its_matches() {
+        let mut network = LinearNetwork::new(vec![Remediate::new(&["crashloop"])]);
+        network.on_assert(&alert("inc-1", "crashloop", 1));
+        network.on_assert(&alert("inc-1", "crashloop", 2));
+        assert_eq!(network.activated().len(), 1);
+
+        assert!(network.on_assert(&alert("inc-1", "oom", 2)).is_empty());
+        assert!(network.activated().is_empty());
+        assert_eq!(network.on_retract(&"inc-1".to_string()).len(), 0);
+    }
+}
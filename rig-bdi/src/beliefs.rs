@@ -0,0 +1,13 @@
+use rig_planner::WorldState;
+use rig_rete::Fact;
+
+/// What the agent currently believes, revised fact by fact.
+pub trait BeliefBase {
+    type Fact: Fact;
+
+    /// Fold a newly perceived fact into the beliefs.
+    fn revise(&mut self, fact: &Self::Fact);
+
+    /// Propositions believed about `scope`, as a planning state.
+    fn world_state(&self, scope: &str) -> WorldState;
+}
@@ -0,0 +1,58 @@
+use crate::beliefs::BeliefBase;
+use crate::desire::Desire;
+use crate::intention::Intention;
+use serde::{Deserialize, Serialize};
+
+/// When an adopted intention is reconsidered, checked between its steps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Commitment {
+    /// Keep going whatever new facts arrive, until the plan ends.
+    #[default]
+    Blind,
+    /// Drop the intention once its remaining steps can no longer reach the
+    /// desire's goals from what is now believed.
+    SingleMinded,
+    /// Drop the intention once the desire behind it changes, so the changed
+    /// desire can be planned for afresh.
+    OpenMinded,
+}
+
+impl Commitment {
+    /// Why `intention` should be dropped, if it should.
+    pub fn reconsider<B: BeliefBase>(
+        self,
+        intention: &Intention,
+        beliefs: &B,
+        desires: &[Desire],
+    ) -> Option<String> {
+        match self {
+            Commitment::Blind => None,
+            Commitment::SingleMinded => {
+                let remaining = intention.remaining();
+                let mut state = beliefs.world_state(&intention.desire.scope);
+                for step in intention.completed() {
+                    state = state.apply(&intention.plan.steps[step]);
+                }
+                let sim = rig_planner::simulate(&state, &remaining, &intention.desire.goals);
+                if sim.succeeds() {
+                    return None;
+                }
+                let blocked: Vec<&str> = sim
+                    .steps
+                    .iter()
+                    .filter(|s| !s.violated_preconditions.is_empty())
+                    .map(|s| s.name.as_str())
+                    .collect();
+                Some(if blocked.is_empty() {
+                    format!("goals no longer reachable: {}", sim.goals_unmet.join(", "))
+                } else {
+                    format!("steps no longer possible: {}", blocked.join(", "))
+                })
+            }
+            Commitment::OpenMinded => {
+                let current = desires.iter().find(|d| d.scope == intention.desire.scope)?;
+                (current != &intention.desire).then(|| "desire changed".to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::intention::Intention;
+use rig_planner::ActionSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Step params that name the resource a step acts on.
+pub const RESOURCE_PARAMS: &[&str] = &["namespace", "deployment", "service"];
+
+/// A step of one scope's plan.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub scope: String,
+    pub step: String,
+}
+
+/// Two plans pulling the same proposition in opposite directions on the
+/// same resource, such as one scaling a deployment up while the other
+/// scales it down.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub proposition: String,
+    /// The [`RESOURCE_PARAMS`] either step is bound to; empty when neither
+    /// names its resource.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resource: BTreeMap<String, String>,
+    pub added_by: PlanStep,
+    pub deleted_by: PlanStep,
+}
+
+/// Every proposition that a remaining step of one intention adds and a
+/// remaining step of the other deletes, where the two steps may act on the
+/// same resource: steps bound to different values of a resource param act
+/// on different resources, and a step without one may act on any.
+pub fn conflicts(a: &Intention, b: &Intention) -> Vec<Conflict> {
+    let (a_steps, b_steps) = (a.remaining(), b.remaining());
+    let mut out = opposing(&a.desire.scope, &a_steps, &b.desire.scope, &b_steps);
+    out.extend(opposing(
+        &b.desire.scope,
+        &b_steps,
+        &a.desire.scope,
+        &a_steps,
+    ));
+    out
+}
+
+fn opposing(
+    adder: &str,
+    adds: &[ActionSchema],
+    deleter: &str,
+    deletes: &[ActionSchema],
+) -> Vec<Conflict> {
+    let mut out = Vec::new();
+    for added in adds {
+        for deleted in deletes {
+            let Some(resource) = shared_resource(added, deleted) else {
+                continue;
+            };
+            for prop in &added.add_effects {
+                if deleted.delete_effects.contains(prop) {
+                    out.push(Conflict {
+                        proposition: prop.clone(),
+                        resource: resource.clone(),
+                        added_by: PlanStep {
+                            scope: adder.to_string(),
+                            step: added.name.clone(),
+                        },
+                        deleted_by: PlanStep {
+                            scope: deleter.to_string(),
+                            step: deleted.name.clone(),
+                        },
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The resource both steps act on, or `None` when their params place them
+/// on different ones.
+fn shared_resource(a: &ActionSchema, b: &ActionSchema) -> Option<BTreeMap<String, String>> {
+    let mut resource = BTreeMap::new();
+    for key in RESOURCE_PARAMS {
+        match (a.params.get(*key), b.params.get(*key)) {
+            (Some(x), Some(y)) if x != y => return None,
+            (Some(value), _) | (None, Some(value)) => {
+                resource.insert(key.to_string(), value.clone());
+            }
+            (None, None) => {}
+        }
+    }
+    Some(resource)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desire::Desire;
+    use rig_effects::Effect;
+    use rig_planner::Plan;
+
+    fn intention(scope: &str, steps: Vec<ActionSchema>) -> Intention {
+        Intention::new(
+            Desire::new(scope, Vec::new()),
+            Plan {
+                total_cost: 0,
+                steps,
+            },
+        )
+    }
+
+    #[test]
+    fn opposing_effects_on_a_proposition_conflict() {
+        let scale_up = ActionSchema::new("scale-up", Effect::Mutate)
+            .adds("capacity_raised")
+            .deletes("capacity_reduced");
+        let scale_down = ActionSchema::new("scale-down", Effect::Mutate)
+            .adds("capacity_reduced")
+            .deletes("capacity_raised");
+        let inspect = ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs");
+
+        let recover = intention("recover", vec![inspect.clone(), scale_up]);
+        let save = intention("save-cost", vec![scale_down]);
+        let found = conflicts(&recover, &save);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].proposition, "capacity_raised");
+        assert_eq!(found[0].added_by.scope, "recover");
+        assert_eq!(found[1].deleted_by.step, "scale-up");
+
+        let mut done = recover.clone();
+        done.complete(0);
+        done.complete(1);
+        assert!(conflicts(&done, &save).is_empty());
+        assert!(conflicts(&intention("observe", vec![inspect]), &save).is_empty());
+    }
+
+    #[test]
+    fn opposing_effects_on_different_resources_do_not_conflict() {
+        let on = |deployment: &str, step: ActionSchema| {
+            step.param("namespace", "shop")
+                .param("deployment", deployment)
+        };
+        let scale_up = |deployment: &str| {
+            let step = ActionSchema::new("scale-up", Effect::Mutate)
+                .adds("capacity_raised")
+                .deletes("capacity_reduced");
+            intention(deployment, vec![on(deployment, step)])
+        };
+        let scale_down = |deployment: &str| {
+            let step = ActionSchema::new("scale-down", Effect::Mutate)
+                .adds("capacity_reduced")
+                .deletes("capacity_raised");
+            intention("save-cost", vec![on(deployment, step)])
+        };
+
+        assert!(conflicts(&scale_up("checkout"), &scale_down("search")).is_empty());
+        let found = conflicts(&scale_up("checkout"), &scale_down("checkout"));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].resource["deployment"], "checkout");
+        assert_eq!(found[0].resource["namespace"], "shop");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::goal::Goal;
+use chrono::{DateTime, Duration, Utc};
+use rig_effects::{Effect, Effectful};
+use serde::{Deserialize, Serialize};
+
+/// A state of affairs the agent would like to bring about.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Desire {
+    /// What the desire is about, such as an incident ID.
+    pub scope: String,
+    /// Propositions that must all hold for the desire to be met.
+    pub goals: Vec<String>,
+    /// Higher is more pressing.
+    pub priority: i32,
+    /// When the goals should be met by; sooner is more pressing among
+    /// desires of equal priority.
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl Desire {
+    pub fn new(scope: impl Into<String>, goals: Vec<String>) -> Self {
+        Self {
+            scope: scope.into(),
+            goals,
+            priority: 0,
+            deadline: None,
+        }
+    }
+
+    /// A desire for `goal` and its sub-goals, raised at `raised_at`, due by
+    /// the tightest deadline in the hierarchy.
+    pub fn for_goal(scope: impl Into<String>, goal: &Goal, raised_at: DateTime<Utc>) -> Self {
+        Self {
+            scope: scope.into(),
+            goals: goal.propositions(),
+            priority: goal.priority,
+            deadline: goal
+                .earliest_deadline()
+                .and_then(|secs| i64::try_from(secs).ok())
+                .map(|secs| raised_at + Duration::seconds(secs)),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Higher priority, or equal priority and due sooner. A desire with a
+    /// deadline is due sooner than one without.
+    pub fn outranks(&self, other: &Desire) -> bool {
+        if self.priority != other.priority {
+            return self.priority > other.priority;
+        }
+        match (self.deadline, other.deadline) {
+            (Some(ours), Some(theirs)) => ours < theirs,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Desires are what rules fire; adopting one touches nothing outside the agent.
+impl Effectful for Desire {
+    fn effect(&self) -> Effect {
+        Effect::Pure
+    }
+}
@@ -0,0 +1,58 @@
+use crate::beliefs::BeliefBase;
+use crate::conflict::Conflict;
+use crate::desire::Desire;
+use crate::intention::Intention;
+use std::sync::mpsc::Receiver;
+
+/// How an intention ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The plan ran and the desire is met.
+    Achieved,
+    /// The intention was given up, and why.
+    Dropped(String),
+}
+
+/// Where facts come from.
+pub trait Percepts<F> {
+    /// Wait for the next fact; `None` once no more will arrive.
+    fn next(&mut self) -> Option<F>;
+
+    /// A fact that has already arrived, without waiting.
+    fn poll(&mut self) -> Option<F>;
+}
+
+impl<F> Percepts<F> for Receiver<F> {
+    fn next(&mut self) -> Option<F> {
+        self.recv().ok()
+    }
+
+    fn poll(&mut self) -> Option<F> {
+        self.try_recv().ok()
+    }
+}
+
+/// What the agent acts on, and what hears about its decisions.
+pub trait Environment<B: BeliefBase, E> {
+    /// Run the intention's next steps, marking the ones that finish.
+    /// `None` while there is more to do. Steps run to completion before this
+    /// returns, so the agent only ever switches intentions between steps.
+    fn step(&mut self, beliefs: &B, intention: &mut Intention) -> Option<Outcome>;
+
+    fn perceived(&mut self, _fact: &B::Fact) {}
+
+    fn adopted(&mut self, _intention: &Intention) {}
+
+    /// `intention` was set aside between steps for the higher-priority `by`;
+    /// it resumes once nothing outranks it.
+    fn preempted(&mut self, _intention: &Intention, _by: &Intention) {}
+
+    fn ended(&mut self, _intention: &Intention, _outcome: &Outcome) {}
+
+    /// `desire` was discarded because no plan for it was found.
+    fn unplanned(&mut self, _desire: &Desire, _error: E) {}
+
+    /// `desire` was discarded, and the intention `with` dropped, because
+    /// their plans have opposing effects; neither runs.
+    fn conflicted(&mut self, _desire: &Desire, _with: &Intention, _conflicts: &[Conflict]) {}
+}
@@ -0,0 +1,131 @@
+use rig_planner::WorldState;
+use serde::{Deserialize, Serialize};
+
+/// A goal proposition with the sub-goals to reach on the way to it.
+///
+/// Sub-goals are reached in descending priority before their parent, and a
+/// goal only applies once its `when` propositions hold.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Goal {
+    /// Proposition that holds once the goal is met.
+    pub name: String,
+    /// Higher is more pressing, among siblings and among root goals.
+    #[serde(default)]
+    pub priority: i32,
+    /// Seconds after the triggering fact by which the goal should be met.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_secs: Option<u64>,
+    /// Propositions that must hold for the goal to apply at all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subgoals: Vec<Goal>,
+}
+
+impl Goal {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            priority: 0,
+            deadline_secs: None,
+            when: Vec::new(),
+            subgoals: Vec::new(),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn within(mut self, secs: u64) -> Self {
+        self.deadline_secs = Some(secs);
+        self
+    }
+
+    pub fn when(mut self, prop: impl Into<String>) -> Self {
+        self.when.push(prop.into());
+        self
+    }
+
+    pub fn subgoal(mut self, goal: Goal) -> Self {
+        self.subgoals.push(goal);
+        self
+    }
+
+    /// Every proposition in the hierarchy, in the order they should be
+    /// reached: sub-goals by descending priority (earliest on ties), each
+    /// before its parent.
+    pub fn propositions(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        self.collect(&mut out);
+        out
+    }
+
+    fn collect(&self, out: &mut Vec<String>) {
+        let mut subgoals: Vec<&Goal> = self.subgoals.iter().collect();
+        subgoals.sort_by_key(|g| std::cmp::Reverse(g.priority));
+        for goal in subgoals {
+            goal.collect(out);
+        }
+        if !out.contains(&self.name) {
+            out.push(self.name.clone());
+        }
+    }
+
+    /// The tightest deadline anywhere in the hierarchy.
+    pub fn earliest_deadline(&self) -> Option<u64> {
+        self.subgoals
+            .iter()
+            .filter_map(Goal::earliest_deadline)
+            .chain(self.deadline_secs)
+            .min()
+    }
+
+    pub fn applies(&self, state: &WorldState) -> bool {
+        self.when.iter().all(|prop| state.has(prop))
+    }
+
+    /// The highest-priority goal among `goals` that applies in `state`; the
+    /// earliest listed wins ties.
+    pub fn select<'a>(goals: &'a [Goal], state: &WorldState) -> Option<&'a Goal> {
+        goals
+            .iter()
+            .filter(|g| g.applies(state))
+            .reduce(|best, g| if g.priority > best.priority { g } else { best })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hierarchy_orders_subgoals_and_selects_by_priority() {
+        let recover = Goal::new("recovery_verified")
+            .with_priority(2)
+            .when("crashloop_detected")
+            .within(900)
+            .subgoal(Goal::new("root_cause_known").with_priority(1))
+            .subgoal(
+                Goal::new("remediation_applied")
+                    .with_priority(5)
+                    .within(300),
+            );
+        assert_eq!(
+            recover.propositions(),
+            vec![
+                "remediation_applied",
+                "root_cause_known",
+                "recovery_verified"
+            ]
+        );
+        assert_eq!(recover.earliest_deadline(), Some(300));
+
+        let goals = vec![Goal::new("acknowledged"), recover];
+        let crashloop: WorldState = ["crashloop_detected".to_string()].into_iter().collect();
+        let select = |state: &WorldState| Goal::select(&goals, state).map(|g| g.name.as_str());
+        assert_eq!(select(&crashloop), Some("recovery_verified"));
+        assert_eq!(select(&WorldState::default()), Some("acknowledged"));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::desire::Desire;
+use rig_planner::{ActionSchema, PartialOrderPlan, Plan};
+use serde::{Deserialize, Serialize};
+
+/// A desire the agent has committed to, the plan it is following, and which
+/// of that plan's steps have run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Intention {
+    pub desire: Desire,
+    pub plan: Plan,
+    done: Vec<bool>,
+}
+
+impl Intention {
+    pub fn new(desire: Desire, plan: Plan) -> Self {
+        Self {
+            done: vec![false; plan.steps.len()],
+            desire,
+            plan,
+        }
+    }
+
+    /// Steps that can run now, per the plan's [`PartialOrderPlan`].
+    pub fn ready(&self) -> Vec<usize> {
+        PartialOrderPlan::from_plan(&self.plan).ready(&self.done)
+    }
+
+    pub fn complete(&mut self, step: usize) {
+        if let Some(done) = self.done.get_mut(step) {
+            *done = true;
+        }
+    }
+
+    /// Whether each step has run, by position.
+    pub fn done(&self) -> &[bool] {
+        &self.done
+    }
+
+    pub fn completed(&self) -> Vec<usize> {
+        (0..self.done.len()).filter(|&i| self.done[i]).collect()
+    }
+
+    /// Steps still to run, in plan order.
+    pub fn remaining(&self) -> Vec<ActionSchema> {
+        (0..self.done.len())
+            .filter(|&i| !self.done[i])
+            .map(|i| self.plan.steps[i].clone())
+            .collect()
+    }
+
+    /// `(steps done, steps planned)`.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.done.iter().filter(|d| **d).count(), self.done.len())
+    }
+
+    pub fn is_achieved(&self) -> bool {
+        self.done.iter().all(|d| *d)
+    }
+
+    /// Commit to a new plan for the same desire, with nothing done yet.
+    pub fn revise(&mut self, plan: Plan) {
+        self.done = vec![false; plan.steps.len()];
+        self.plan = plan;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig_effects::Effect;
+    use rig_planner::ActionSchema;
+
+    #[test]
+    fn tracks_progress_through_the_plan() {
+        let plan = Plan {
+            steps: vec![
+                ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+                ActionSchema::new("rollback-deployment", Effect::Mutate)
+                    .requires("has_pod_logs")
+                    .adds("remediation_applied"),
+            ],
+            total_cost: 12,
+        };
+        let mut intention = Intention::new(Desire::new("inc-1", Vec::new()), plan.clone());
+        assert_eq!(intention.ready(), vec![0]);
+
+        intention.complete(0);
+        assert_eq!(intention.ready(), vec![1]);
+        assert_eq!(intention.progress(), (1, 2));
+        assert_eq!(intention.remaining(), plan.steps[1..].to_vec());
+        assert!(!intention.is_achieved());
+
+        intention.complete(1);
+        assert!(intention.is_achieved());
+        assert_eq!(intention.completed(), vec![0, 1]);
+
+        intention.revise(plan);
+        assert_eq!(intention.progress(), (0, 2));
+    }
+}
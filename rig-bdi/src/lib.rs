@@ -1,14 +1,39 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Belief-desire-intention agents over [`rig_rete`] and [`rig_planner`].
+//!
+//! A [`BeliefBase`] is revised by every perceived fact. The same fact runs
+//! through a [`rig_rete::ReteNetwork`] whose rules fire [`Desire`]s; the
+//! most pressing desire is handed to a [`Planner`], and the resulting plan is
+//! committed to as an [`Intention`] that tracks which steps have run.
+//! [`Agent::run`] strings these together as perceive → deliberate → plan →
+//! act, interleaving one intention per scope through an [`Environment`],
+//! reconsidering them between steps under a [`Commitment`] strategy and
+//! letting higher-priority intentions preempt lower ones.
+//!
+//! Desires can be raised from a [`Goal`] hierarchy with priorities and
+//! deadlines. A desire whose plan [`conflicts`] with a running intention,
+//! adding what the other deletes, is rejected together with it.
+//!
+//! [`LinearNetwork`] is a rule-at-a-time network for small rule sets, and
+//! [`StripsPlanner`] plans with [`rig_planner::plan_expected`].
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod agent;
+pub mod beliefs;
+pub mod commitment;
+pub mod conflict;
+pub mod desire;
+pub mod environment;
+pub mod goal;
+pub mod intention;
+pub mod network;
+pub mod planner;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use agent::{Agent, Rejection};
+pub use beliefs::BeliefBase;
+pub use commitment::Commitment;
+pub use conflict::{Conflict, PlanStep, RESOURCE_PARAMS, conflicts};
+pub use desire::Desire;
+pub use environment::{Environment, Outcome, Percepts};
+pub use goal::Goal;
+pub use intention::Intention;
+pub use network::LinearNetwork;
+pub use planner::{Planner, StripsPlanner};
@@ -0,0 +1,94 @@
+use chrono::Utc;
+use rig_rete::{Fact, ReteNetwork, Rule, RuleMatch};
+
+/// A [`ReteNetwork`] that tests every rule against each asserted fact.
+///
+/// Rules match one fact at a time: every condition must accept it, and the
+/// last condition's bindings are the match's bindings (a rule without
+/// conditions matches with default bindings). Re-asserting a fact replaces
+/// the matches it caused before.
+pub struct LinearNetwork<R: Rule> {
+    rules: Vec<R>,
+    activated: Vec<RuleMatch<R>>,
+}
+
+impl<R: Rule> Default for LinearNetwork<R> {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            activated: Vec::new(),
+        }
+    }
+}
+
+impl<R: Rule> LinearNetwork<R> {
+    pub fn new(rules: Vec<R>) -> Self {
+        Self {
+            rules,
+            activated: Vec::new(),
+        }
+    }
+}
+
+impl<R> ReteNetwork<R::Fact, R> for LinearNetwork<R>
+where
+    R: Rule + Clone,
+    R::Bindings: Default,
+{
+    fn add_rule(&mut self, rule: R) {
+        self.rules.push(rule);
+    }
+
+    fn remove_rule(&mut self, rule_id: &str) -> Option<R> {
+        let pos = self.rules.iter().position(|r| r.id() == rule_id)?;
+        self.activated.retain(|m| m.rule.id() != rule_id);
+        Some(self.rules.remove(pos))
+    }
+
+    fn on_assert(&mut self, fact: &R::Fact) -> Vec<RuleMatch<R>> {
+        self.activated
+            .retain(|m| !m.matched_facts.contains(fact.id()));
+
+        let timestamp = Utc::now();
+        let matches: Vec<RuleMatch<R>> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let mut bindings = R::Bindings::default();
+                for condition in rule.conditions() {
+                    bindings = condition.matches(fact)?;
+                }
+                Some(RuleMatch {
+                    rule: rule.clone(),
+                    bindings,
+                    matched_facts: vec![fact.id().clone()],
+                    timestamp,
+                })
+            })
+            .collect();
+
+        self.activated.extend(matches.iter().map(duplicate));
+        matches
+    }
+
+    fn on_retract(&mut self, fact_id: &<R::Fact as Fact>::Id) -> Vec<RuleMatch<R>> {
+        let (removed, kept) = std::mem::take(&mut self.activated)
+            .into_iter()
+            .partition(|m| m.matched_facts.contains(fact_id));
+        self.activated = kept;
+        removed
+    }
+
+    fn activated(&self) -> &[RuleMatch<R>] {
+        &self.activated
+    }
+}
+
+fn duplicate<R: Rule + Clone>(m: &RuleMatch<R>) -> RuleMatch<R> {
+    RuleMatch {
+        rule: m.rule.clone(),
+        bindings: m.bindings.clone(),
+        matched_facts: m.matched_facts.clone(),
+        timestamp: m.timestamp,
+    }
+}
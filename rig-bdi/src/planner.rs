@@ -0,0 +1,58 @@
+use crate::beliefs::BeliefBase;
+use crate::desire::Desire;
+use rig_planner::{ActionSchema, OutcomeModel, Plan, Unreachable, plan_expected, shape};
+
+/// Turns a desire into a plan under the current beliefs.
+pub trait Planner<B> {
+    /// Why no plan was found.
+    type Error;
+
+    /// Plan for `desire` from the beliefs with the effects of `done` applied:
+    /// steps an earlier intention for the same scope already carried out.
+    fn plan(
+        &self,
+        beliefs: &B,
+        desire: &Desire,
+        done: &[ActionSchema],
+    ) -> Result<Plan, Self::Error>;
+}
+
+/// Expected-cost search over a fixed action set, shaped for execution.
+#[derive(Clone, Debug, Default)]
+pub struct StripsPlanner {
+    pub actions: Vec<ActionSchema>,
+    pub outcomes: OutcomeModel,
+}
+
+impl StripsPlanner {
+    pub fn new(actions: Vec<ActionSchema>) -> Self {
+        Self {
+            actions,
+            outcomes: OutcomeModel::new(),
+        }
+    }
+
+    pub fn with_outcomes(mut self, outcomes: OutcomeModel) -> Self {
+        self.outcomes = outcomes;
+        self
+    }
+}
+
+impl<B: BeliefBase> Planner<B> for StripsPlanner {
+    type Error = Unreachable;
+
+    fn plan(
+        &self,
+        beliefs: &B,
+        desire: &Desire,
+        done: &[ActionSchema],
+    ) -> Result<Plan, Unreachable> {
+        let state = done
+            .iter()
+            .fold(beliefs.world_state(&desire.scope), |state, step| {
+                state.apply(step)
+            });
+        plan_expected(&state, &desire.goals, &self.actions, &self.outcomes)
+            .map(|found| shape(&found.plan))
+    }
+}
@@ -0,0 +1,180 @@
+use crate::probabilistic::{ExpectedPlan, OutcomeModel};
+use crate::search::{Plan, Unreachable, actions_along, plan, successors};
+use crate::strips::{ActionSchema, WorldState};
+use pathfinding::prelude::yen;
+use rig_effects::Effect;
+use serde::{Deserialize, Serialize};
+
+// ── Alternative ──────────────────────────────────────────────────────────────
+
+/// One of several candidate plans, described relative to the best one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alternative {
+    /// "A" for the best plan, then "B", "C", ...
+    pub label: String,
+    pub plan: Plan,
+    pub mutate_steps: usize,
+    pub irreversible_steps: usize,
+    /// See [`ExpectedPlan`]. Absent from alternatives recorded before
+    /// outcome tracking, which read back as zero.
+    #[serde(default)]
+    pub expected_cost: f64,
+    #[serde(default)]
+    pub success_probability: f64,
+    pub diff: PlanDiff,
+}
+
+/// How a plan differs from the best plan, by step name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanDiff {
+    /// Steps this plan runs that the best plan does not.
+    pub added: Vec<String>,
+    /// Steps of the best plan that this plan skips.
+    pub removed: Vec<String>,
+    /// `total_cost` minus the best plan's `total_cost`.
+    pub cost_delta: i64,
+}
+
+// ── Search ───────────────────────────────────────────────────────────────────
+
+/// Up to `k` loopless plans in increasing cost order, via Yen's algorithm
+/// over the same state graph as [`plan`].
+pub fn k_best(
+    initial: &WorldState,
+    goals: &[String],
+    actions: &[ActionSchema],
+    k: usize,
+) -> Result<Vec<Plan>, Unreachable> {
+    let found = yen(
+        initial,
+        |state| successors(state, actions, ActionSchema::cost),
+        |state| state.satisfies(goals),
+        k,
+    );
+
+    if found.is_empty() {
+        // Let the single-plan search produce the explanation.
+        return plan(initial, goals, actions).map(|best| vec![best]);
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(path, total_cost)| Plan {
+            steps: actions_along(&path, actions, ActionSchema::cost),
+            total_cost,
+        })
+        .collect())
+}
+
+/// Label `plans` (best first) and describe each against the first, with
+/// outcome statistics from `model`.
+pub fn compare(plans: &[Plan], model: &OutcomeModel) -> Vec<Alternative> {
+    let Some(best) = plans.first() else {
+        return Vec::new();
+    };
+
+    plans
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let assessed = ExpectedPlan::assess(candidate.clone(), model);
+            Alternative {
+                label: label(index),
+                mutate_steps: count(candidate, Effect::Mutate),
+                irreversible_steps: count(candidate, Effect::Irreversible),
+                expected_cost: assessed.expected_cost,
+                success_probability: assessed.success_probability,
+                diff: diff(best, candidate),
+                plan: assessed.plan,
+            }
+        })
+        .collect()
+}
+
+fn label(index: usize) -> String {
+    match u8::try_from(index) {
+        Ok(i) if i < 26 => char::from(b'A' + i).to_string(),
+        _ => format!("#{}", index + 1),
+    }
+}
+
+fn count(plan: &Plan, effect: Effect) -> usize {
+    plan.steps.iter().filter(|s| s.effect == effect).count()
+}
+
+fn diff(best: &Plan, candidate: &Plan) -> PlanDiff {
+    let missing_from = |plan: &Plan, other: &Plan| {
+        plan.steps
+            .iter()
+            .filter(|s| !other.steps.iter().any(|o| o.name == s.name))
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    PlanDiff {
+        added: missing_from(candidate, best),
+        removed: missing_from(best, candidate),
+        cost_delta: i64::from(candidate.total_cost) - i64::from(best.total_cost),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domain() -> Vec<ActionSchema> {
+        vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop_detected")
+                .adds("has_pod_logs"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+            ActionSchema::new("delete-pod", Effect::Irreversible)
+                .requires("crashloop_detected")
+                .adds("remediation_applied"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("remediation_applied")
+                .adds("recovery_verified"),
+        ]
+    }
+
+    #[test]
+    fn returns_plans_in_cost_order_with_diffs() {
+        let initial: WorldState = ["crashloop_detected"].into_iter().collect();
+        let plans = k_best(&initial, &["recovery_verified".to_string()], &domain(), 3).unwrap();
+        assert!(plans.len() >= 2);
+        assert!(plans.windows(2).all(|w| w[0].total_cost <= w[1].total_cost));
+
+        let mut model = OutcomeModel::new();
+        model.record("delete-pod", false);
+        let alternatives = compare(&plans, &model);
+        assert_eq!(alternatives[0].label, "A");
+        assert_eq!(alternatives[0].diff, PlanDiff::default());
+        assert_eq!(alternatives[0].mutate_steps, 1);
+
+        let b = &alternatives[1];
+        assert_eq!(b.label, "B");
+        assert_eq!(b.irreversible_steps, 1);
+        assert_eq!(b.success_probability, 0.5);
+        assert_eq!(b.expected_cost, f64::from(b.plan.total_cost) + 100.0);
+        assert!(b.diff.added.contains(&"delete-pod".to_string()));
+        assert!(b.diff.removed.contains(&"rollback-deployment".to_string()));
+        assert_eq!(
+            b.diff.cost_delta,
+            i64::from(b.plan.total_cost) - i64::from(plans[0].total_cost)
+        );
+    }
+
+    #[test]
+    fn unreachable_goal_is_still_explained() {
+        let err = k_best(
+            &WorldState::default(),
+            &["recovery_verified".to_string()],
+            &domain(),
+            3,
+        )
+        .unwrap_err();
+        assert_eq!(err.missing_goals, vec!["recovery_verified".to_string()]);
+    }
+}
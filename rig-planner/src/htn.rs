@@ -0,0 +1,304 @@
+use crate::search::Plan;
+use crate::strips::{ActionSchema, WorldState};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Method nesting after which a branch is abandoned. Guards against methods
+/// that (directly or indirectly) expand into themselves forever; primitive
+/// tasks, however many, do not count.
+const MAX_DEPTH: usize = 64;
+
+// ── Task ─────────────────────────────────────────────────────────────────────
+
+/// A node in a task network: either directly executable or decomposed by methods.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Task {
+    Primitive(ActionSchema),
+    Compound(String),
+}
+
+impl Task {
+    pub fn compound(name: impl Into<String>) -> Self {
+        Task::Compound(name.into())
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Task::Primitive(action) => &action.name,
+            Task::Compound(name) => name,
+        }
+    }
+}
+
+impl From<ActionSchema> for Task {
+    fn from(action: ActionSchema) -> Self {
+        Task::Primitive(action)
+    }
+}
+
+// ── Method ───────────────────────────────────────────────────────────────────
+
+/// One way to accomplish a compound task, applicable when its preconditions hold.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Method {
+    pub name: String,
+    pub preconditions: Vec<String>,
+    pub subtasks: Vec<Task>,
+}
+
+impl Method {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            preconditions: Vec::new(),
+            subtasks: Vec::new(),
+        }
+    }
+
+    pub fn requires(mut self, prop: impl Into<String>) -> Self {
+        self.preconditions.push(prop.into());
+        self
+    }
+
+    pub fn then(mut self, task: impl Into<Task>) -> Self {
+        self.subtasks.push(task.into());
+        self
+    }
+}
+
+// ── HtnDomain ────────────────────────────────────────────────────────────────
+
+/// Compound tasks and their methods, tried in insertion order.
+#[derive(Clone, Debug, Default)]
+pub struct HtnDomain {
+    methods: BTreeMap<String, Vec<Method>>,
+}
+
+/// Why a task network could not be decomposed into an executable plan.
+///
+/// `trace` lists every dead end hit while backtracking, in the order found.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecompositionFailure {
+    pub task: String,
+    pub trace: Vec<String>,
+}
+
+impl fmt::Display for DecompositionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot decompose '{}'", self.task)?;
+        if let Some(last) = self.trace.last() {
+            write!(f, ": {last}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecompositionFailure {}
+
+impl HtnDomain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn method(mut self, task: impl Into<String>, method: Method) -> Self {
+        self.methods.entry(task.into()).or_default().push(method);
+        self
+    }
+
+    pub fn methods_for(&self, task: &str) -> &[Method] {
+        self.methods
+            .get(task)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Decompose `root` into primitive actions, depth-first, simulating each
+    /// primitive's effects so later preconditions see the updated state.
+    /// When a method's subtree fails, the next method for that task is tried.
+    pub fn decompose(
+        &self,
+        initial: &WorldState,
+        root: &Task,
+    ) -> Result<Plan, DecompositionFailure> {
+        let mut trace = Vec::new();
+        match self.seek(initial, vec![(root.clone(), 0)], &mut trace) {
+            Some(steps) => Ok(Plan {
+                total_cost: steps.iter().map(ActionSchema::cost).sum(),
+                steps,
+            }),
+            None => Err(DecompositionFailure {
+                task: root.name().to_string(),
+                trace,
+            }),
+        }
+    }
+
+    /// `agenda` pairs each task with the number of methods it was expanded
+    /// through.
+    fn seek(
+        &self,
+        state: &WorldState,
+        mut agenda: Vec<(Task, usize)>,
+        trace: &mut Vec<String>,
+    ) -> Option<Vec<ActionSchema>> {
+        if agenda.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let (task, depth) = agenda.remove(0);
+        match task {
+            Task::Primitive(action) => {
+                let missing = missing(&action.preconditions, state);
+                if !missing.is_empty() {
+                    trace.push(format!(
+                        "primitive '{}' missing preconditions: {}",
+                        action.name,
+                        missing.join(", ")
+                    ));
+                    return None;
+                }
+                let next = state.apply(&action);
+                let mut rest = self.seek(&next, agenda, trace)?;
+                rest.insert(0, action);
+                Some(rest)
+            }
+            Task::Compound(_) if depth > MAX_DEPTH => {
+                trace.push(format!("decomposition exceeded depth {MAX_DEPTH}"));
+                None
+            }
+            Task::Compound(name) => {
+                let methods = self.methods_for(&name);
+                if methods.is_empty() {
+                    trace.push(format!("no methods for compound task '{name}'"));
+                    return None;
+                }
+                for method in methods {
+                    let missing = missing(&method.preconditions, state);
+                    if !missing.is_empty() {
+                        trace.push(format!(
+                            "method '{}' for '{name}' missing preconditions: {}",
+                            method.name,
+                            missing.join(", ")
+                        ));
+                        continue;
+                    }
+                    let mut expanded: Vec<(Task, usize)> = method
+                        .subtasks
+                        .iter()
+                        .map(|task| (task.clone(), depth + 1))
+                        .collect();
+                    expanded.extend(agenda.iter().cloned());
+                    if let Some(steps) = self.seek(state, expanded, trace) {
+                        return Some(steps);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+fn missing(preconditions: &[String], state: &WorldState) -> Vec<String> {
+    preconditions
+        .iter()
+        .filter(|p| !state.has(p))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig_effects::Effect;
+
+    fn domain() -> HtnDomain {
+        let inspect_logs =
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs");
+        let inspect_events =
+            ActionSchema::new("inspect-pod-events", Effect::Observe).adds("has_pod_events");
+        let rollback = ActionSchema::new("rollback-deployment", Effect::Mutate)
+            .requires("has_deploy_history")
+            .adds("remediation_applied");
+        let scale = ActionSchema::new("scale-deployment", Effect::Mutate)
+            .requires("has_pod_events")
+            .adds("remediation_applied");
+
+        HtnDomain::new()
+            .method(
+                "mitigate-crashloop",
+                Method::new("diagnose-then-remediate")
+                    .requires("crashloop_detected")
+                    .then(Task::compound("diagnose"))
+                    .then(Task::compound("remediate")),
+            )
+            .method(
+                "diagnose",
+                Method::new("logs-and-events")
+                    .then(inspect_logs)
+                    .then(inspect_events),
+            )
+            .method("remediate", Method::new("rollback").then(rollback))
+            .method("remediate", Method::new("scale").then(scale))
+    }
+
+    #[test]
+    fn backtracks_to_next_method_when_primitive_precondition_fails() {
+        let initial: WorldState = ["crashloop_detected"].into_iter().collect();
+        let plan = domain()
+            .decompose(&initial, &Task::compound("mitigate-crashloop"))
+            .unwrap();
+
+        let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["inspect-pod-logs", "inspect-pod-events", "scale-deployment"]
+        );
+        assert_eq!(plan.total_cost, 2 + 2 + 10);
+    }
+
+    #[test]
+    fn prefers_first_applicable_method() {
+        let initial: WorldState = ["crashloop_detected", "has_deploy_history"]
+            .into_iter()
+            .collect();
+        let plan = domain()
+            .decompose(&initial, &Task::compound("mitigate-crashloop"))
+            .unwrap();
+        assert_eq!(plan.steps.last().unwrap().name, "rollback-deployment");
+    }
+
+    #[test]
+    fn failure_reports_dead_ends() {
+        let err = domain()
+            .decompose(
+                &WorldState::default(),
+                &Task::compound("mitigate-crashloop"),
+            )
+            .unwrap_err();
+        assert_eq!(err.task, "mitigate-crashloop");
+        assert!(err.trace[0].contains("crashloop_detected"));
+    }
+
+    #[test]
+    fn self_recursive_method_is_cut_off() {
+        let domain =
+            HtnDomain::new().method("loop", Method::new("again").then(Task::compound("loop")));
+        let err = domain
+            .decompose(&WorldState::default(), &Task::compound("loop"))
+            .unwrap_err();
+        assert!(err.trace.iter().any(|t| t.contains("exceeded depth")));
+    }
+
+    #[test]
+    fn long_flat_networks_are_not_cut_off() {
+        let method = (0..200).fold(Method::new("every-check"), |method, i| {
+            method.then(ActionSchema::new(format!("check-{i}"), Effect::Observe))
+        });
+        let plan = HtnDomain::new()
+            .method("audit", method)
+            .decompose(&WorldState::default(), &Task::compound("audit"))
+            .unwrap();
+        assert_eq!(plan.steps.len(), 200);
+    }
+}
@@ -1,14 +1,37 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Effect-aware STRIPS planning.
+//!
+//! Actions carry preconditions and add/delete effects over string
+//! propositions, plus the [`rig_effects::Effect`] of executing them. Search
+//! runs A* over [`WorldState`]s with step cost taken from
+//! [`rig_effects::Effect::cost_weight`], so plans prefer fewer severe effects.
+//!
+//! Composite runbooks can instead be expressed as an [`HtnDomain`] of
+//! compound tasks whose methods bottom out in the same action schemas.
+//!
+//! Found plans can be passed through [`shape`], which reorders independent
+//! steps by effect severity and gates every non-backtrackable step.
+//! [`k_best`] and [`compare`] rank runner-up plans against the best one.
+//! [`plan_expected`] weighs steps by how often they have worked before.
+//! [`PartialOrderPlan`] relaxes a plan to the orderings its steps need.
+//!
+//! Domains round-trip through PDDL via the [`pddl`] module, and any step
+//! list can be dry-run against a state with [`simulate()`].
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod alternatives;
+pub mod htn;
+pub mod partial_order;
+pub mod pddl;
+pub mod probabilistic;
+pub mod search;
+pub mod shape;
+pub mod simulate;
+pub mod strips;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use alternatives::{Alternative, PlanDiff, compare, k_best};
+pub use htn::{DecompositionFailure, HtnDomain, Method, Task};
+pub use partial_order::PartialOrderPlan;
+pub use probabilistic::{ExpectedPlan, OutcomeCounts, OutcomeModel, plan_expected};
+pub use search::{BlockedAction, Plan, Unreachable, plan};
+pub use shape::{APPROVAL_GATE_PREFIX, approval_gate, gated_action, shape, shape_steps};
+pub use simulate::{SimulatedStep, Simulation, simulate};
+pub use strips::{ActionSchema, WorldState};
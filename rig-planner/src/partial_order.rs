@@ -0,0 +1,112 @@
+use crate::search::Plan;
+use crate::shape::{depends_on, gated_action};
+use crate::strips::ActionSchema;
+use rig_effects::Effect;
+use serde::{Deserialize, Serialize};
+
+/// A plan as a DAG over its steps. Steps keep their total-order positions;
+/// each edge `(before, after)` has `before < after`, so position order is
+/// always a valid linearization.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialOrderPlan {
+    pub steps: Vec<ActionSchema>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl PartialOrderPlan {
+    /// Keep only the orderings that matter: [`depends_on`] edges, and each
+    /// approval gate ahead of the step it guards. A gate also waits for the
+    /// guarded step's other predecessors and for every earlier step that
+    /// changes state, so approval is asked against the state the step will
+    /// actually run in.
+    pub fn from_steps(steps: &[ActionSchema]) -> Self {
+        let mut edges = Vec::new();
+        for (after, later) in steps.iter().enumerate() {
+            let guarded = gated_action(later)
+                .and_then(|name| steps[after + 1..].iter().find(|step| step.name == name));
+            for (before, earlier) in steps[..after].iter().enumerate() {
+                let guards = gated_action(earlier) == Some(later.name.as_str());
+                let gate_waits = guarded.is_some_and(|guarded| {
+                    depends_on(guarded, earlier)
+                        || !matches!(earlier.effect, Effect::Pure | Effect::Observe)
+                });
+                if guards || gate_waits || depends_on(later, earlier) {
+                    edges.push((before, after));
+                }
+            }
+        }
+
+        Self {
+            steps: steps.to_vec(),
+            edges,
+        }
+    }
+
+    pub fn from_plan(plan: &Plan) -> Self {
+        Self::from_steps(&plan.steps)
+    }
+
+    pub fn predecessors(&self, step: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(_, after)| *after == step)
+            .map(|(before, _)| *before)
+    }
+
+    /// Steps not yet `done` whose predecessors all are, in position order.
+    pub fn ready(&self, done: &[bool]) -> Vec<usize> {
+        (0..self.steps.len())
+            .filter(|&i| !done[i] && self.predecessors(i).all(|p| done[p]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shape::shape_steps;
+
+    #[test]
+    fn independent_observes_share_no_edge() {
+        let steps = vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("inspect-memory-metrics", Effect::Observe).adds("has_memory_metrics"),
+            ActionSchema::new("tune-memory-limits", Effect::Mutate)
+                .requires("has_memory_metrics")
+                .adds("remediation_applied"),
+        ];
+        let dag = PartialOrderPlan::from_steps(&steps);
+
+        assert_eq!(dag.edges, vec![(1, 2)]);
+        assert_eq!(dag.ready(&[false, false, false]), vec![0, 1]);
+        assert_eq!(dag.ready(&[true, true, false]), vec![2]);
+    }
+
+    #[test]
+    fn approval_gate_precedes_its_step() {
+        let steps = shape_steps(&[ActionSchema::new("drain-node", Effect::Irreversible)]);
+        let dag = PartialOrderPlan::from_steps(&steps);
+
+        assert_eq!(dag.edges, vec![(0, 1)]);
+        assert_eq!(dag.predecessors(1).collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn approval_gate_waits_for_what_its_step_waits_on() {
+        let steps = shape_steps(&[
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("scale-down", Effect::Mutate).adds("replicas_reduced"),
+            ActionSchema::new("drain-node", Effect::Irreversible)
+                .requires("has_pod_logs")
+                .requires("replicas_reduced"),
+        ]);
+        let dag = PartialOrderPlan::from_steps(&steps);
+        let gate = steps
+            .iter()
+            .position(|s| s.name == "approve:drain-node")
+            .unwrap();
+
+        assert!(!dag.ready(&vec![false; steps.len()]).contains(&gate));
+        assert_eq!(dag.predecessors(gate).collect::<Vec<_>>(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,716 @@
+//! PDDL export and import for STRIPS action schemas.
+//!
+//! Propositions map to ground atoms by splitting on `:`, so
+//! `failed:rollback-deployment` is `(failed rollback-deployment)` and
+//! `crashloop_detected` is `(crashloop_detected)`. PDDL has no notion of
+//! [`Effect`], so effects travel in a side table keyed by action name.
+//!
+//! The importer covers the STRIPS subset with typing: `:types`,
+//! `:constants`, `:objects`, parameterized actions, conjunctive positive
+//! preconditions and add/delete effects. Lifted actions are grounded over
+//! every type-compatible object tuple.
+
+use crate::strips::{ActionSchema, WorldState};
+use rig_effects::Effect;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+/// Effect annotations for imported actions, keyed by (lifted) action name.
+pub type EffectTable = BTreeMap<String, Effect>;
+
+/// Actions, initial state and goals read from a PDDL domain and problem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PddlImport {
+    pub actions: Vec<ActionSchema>,
+    pub initial: WorldState,
+    pub goals: Vec<String>,
+}
+
+// ── Export ───────────────────────────────────────────────────────────────────
+
+/// Render `actions` as a ground PDDL domain. Action names are made
+/// PDDL-safe with [`pddl_name`]; anything else is emitted as is.
+pub fn export_domain(name: &str, actions: &[ActionSchema]) -> String {
+    let mut predicates = BTreeMap::new();
+    for prop in action_props(actions) {
+        let (predicate, args) = split_prop(prop);
+        predicates.insert(predicate.to_string(), args.len());
+    }
+    let constants = constants(actions);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "(define (domain {})", pddl_name(name));
+    out.push_str("  (:requirements :strips :typing)\n");
+    if !constants.is_empty() {
+        let joined = constants.into_iter().collect::<Vec<_>>().join(" ");
+        let _ = writeln!(out, "  (:constants {joined})");
+    }
+    out.push_str("  (:predicates");
+    for (predicate, arity) in &predicates {
+        let params = (0..*arity).map(|i| format!(" ?a{i}")).collect::<String>();
+        let _ = write!(out, " ({predicate}{params})");
+    }
+    out.push_str(")\n");
+
+    for action in actions {
+        let _ = writeln!(out, "  ; effect: {:?}", action.effect);
+        let _ = writeln!(out, "  (:action {}", pddl_name(&action.name));
+        out.push_str("    :parameters ()\n");
+        let _ = writeln!(
+            out,
+            "    :precondition (and{})",
+            atoms(&action.preconditions, false)
+        );
+        let _ = writeln!(
+            out,
+            "    :effect (and{}{}))",
+            atoms(&action.add_effects, false),
+            atoms(&action.delete_effects, true)
+        );
+    }
+    out.push_str(")\n");
+    out
+}
+
+/// Render a PDDL problem for the domain exported from `actions`, whose
+/// init is `initial` and whose goal is the conjunction of `goals`. Atom
+/// arguments the domain does not already declare as constants are
+/// declared as objects.
+pub fn export_problem(
+    name: &str,
+    domain: &str,
+    actions: &[ActionSchema],
+    initial: &WorldState,
+    goals: &[String],
+) -> String {
+    let constants = constants(actions);
+    let objects = initial
+        .props()
+        .iter()
+        .chain(goals)
+        .flat_map(|p| split_prop(p).1)
+        .filter(|arg| !constants.contains(arg))
+        .collect::<BTreeSet<_>>();
+    let init = initial.props().iter().cloned().collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "(define (problem {})", pddl_name(name));
+    let _ = writeln!(out, "  (:domain {})", pddl_name(domain));
+    if !objects.is_empty() {
+        let joined = objects.into_iter().collect::<Vec<_>>().join(" ");
+        let _ = writeln!(out, "  (:objects {joined})");
+    }
+    let _ = writeln!(out, "  (:init{})", atoms(&init, false));
+    let _ = writeln!(out, "  (:goal (and{})))", atoms(goals, false));
+    out
+}
+
+/// The side table [`import`] needs to restore the effects of `actions`,
+/// keyed by the names [`export_domain`] gives them.
+pub fn effect_annotations(actions: &[ActionSchema]) -> EffectTable {
+    actions
+        .iter()
+        .map(|a| (pddl_name(&a.name), a.effect.clone()))
+        .collect()
+}
+
+/// Replace characters PDDL does not allow in names with `-`.
+pub fn pddl_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn action_props(actions: &[ActionSchema]) -> impl Iterator<Item = &String> {
+    actions.iter().flat_map(|action| {
+        action
+            .preconditions
+            .iter()
+            .chain(&action.add_effects)
+            .chain(&action.delete_effects)
+    })
+}
+
+/// Every atom argument in `actions`, which the domain declares as constants.
+fn constants(actions: &[ActionSchema]) -> BTreeSet<&str> {
+    action_props(actions)
+        .flat_map(|prop| split_prop(prop).1)
+        .collect()
+}
+
+fn split_prop(prop: &str) -> (&str, Vec<&str>) {
+    let mut parts = prop.split(':');
+    let predicate = parts.next().unwrap_or_default();
+    (predicate, parts.collect())
+}
+
+fn atoms(props: &[String], negated: bool) -> String {
+    props
+        .iter()
+        .map(|p| {
+            let (predicate, args) = split_prop(p);
+            let atom = std::iter::once(predicate)
+                .chain(args)
+                .collect::<Vec<_>>()
+                .join(" ");
+            if negated {
+                format!(" (not ({atom}))")
+            } else {
+                format!(" ({atom})")
+            }
+        })
+        .collect()
+}
+
+// ── Import ───────────────────────────────────────────────────────────────────
+
+/// Parse a domain and problem, ground the domain's actions over the
+/// problem's objects and attach effects from `effects`. Every action in
+/// the domain must have an annotation; like the rest of PDDL, annotation
+/// names are read without regard to case.
+pub fn import(domain: &str, problem: &str, effects: &EffectTable) -> Result<PddlImport, String> {
+    let domain = parse_domain(&parse_sexpr(domain)?)?;
+    let problem = parse_problem(&parse_sexpr(problem)?)?;
+
+    if let Some(expected) = &problem.domain
+        && *expected != domain.name
+    {
+        return Err(format!(
+            "problem is for domain '{expected}', not '{}'",
+            domain.name
+        ));
+    }
+
+    // A problem may re-declare a domain constant; keep the first declaration.
+    let mut objects: Vec<(String, String)> = Vec::new();
+    for (name, ty) in domain.constants.iter().chain(&problem.objects) {
+        if !objects.iter().any(|(seen, _)| seen == name) {
+            objects.push((name.clone(), ty.clone()));
+        }
+    }
+
+    let effects: BTreeMap<String, &Effect> = effects
+        .iter()
+        .map(|(name, effect)| (name.to_lowercase(), effect))
+        .collect();
+    let mut actions = Vec::new();
+    for action in &domain.actions {
+        let effect = effects
+            .get(&action.name)
+            .copied()
+            .cloned()
+            .ok_or_else(|| format!("no effect annotation for action '{}'", action.name))?;
+        for binding in bindings(&action.parameters, &objects, &domain.types)? {
+            actions.push(action.ground(&binding, effect.clone())?);
+        }
+    }
+
+    let no_vars = BTreeMap::new();
+    let initial = problem
+        .init
+        .iter()
+        .map(|a| ground_atom(a, &no_vars))
+        .collect::<Result<WorldState, _>>()?;
+    let goals = problem
+        .goal
+        .iter()
+        .map(|a| ground_atom(a, &no_vars))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(PddlImport {
+        actions,
+        initial,
+        goals,
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Sexpr {
+    Symbol(String),
+    List(Vec<Sexpr>),
+}
+
+impl Sexpr {
+    fn symbol(&self) -> Option<&str> {
+        match self {
+            Sexpr::Symbol(s) => Some(s),
+            Sexpr::List(_) => None,
+        }
+    }
+
+    fn list(&self) -> Option<&[Sexpr]> {
+        match self {
+            Sexpr::List(items) => Some(items),
+            Sexpr::Symbol(_) => None,
+        }
+    }
+}
+
+/// Parse one s-expression. PDDL is case-insensitive, so symbols are
+/// lowercased; `;` starts a comment running to end of line.
+fn parse_sexpr(src: &str) -> Result<Sexpr, String> {
+    let mut tokens = Vec::new();
+    for line in src.lines() {
+        let code = line.split(';').next().unwrap_or_default();
+        let spaced = code.replace('(', " ( ").replace(')', " ) ");
+        tokens.extend(spaced.split_whitespace().map(str::to_lowercase));
+    }
+
+    let mut stack: Vec<Vec<Sexpr>> = Vec::new();
+    let mut done = None;
+    for token in tokens {
+        if done.is_some() {
+            return Err(format!("unexpected '{token}' after end of definition"));
+        }
+        let item = match token.as_str() {
+            "(" => {
+                stack.push(Vec::new());
+                continue;
+            }
+            ")" => Sexpr::List(stack.pop().ok_or("unbalanced ')'")?),
+            _ => Sexpr::Symbol(token),
+        };
+        match stack.last_mut() {
+            Some(parent) => parent.push(item),
+            None => done = Some(item),
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err("unbalanced '(': missing ')'".into());
+    }
+    done.ok_or_else(|| "empty PDDL input".into())
+}
+
+/// `(pred arg ...)` where args are object names or `?variables`.
+#[derive(Clone, Debug)]
+struct AtomPattern {
+    predicate: String,
+    args: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct LiftedAction {
+    name: String,
+    parameters: Vec<(String, String)>,
+    preconditions: Vec<AtomPattern>,
+    add_effects: Vec<AtomPattern>,
+    delete_effects: Vec<AtomPattern>,
+}
+
+impl LiftedAction {
+    fn ground(
+        &self,
+        binding: &BTreeMap<String, String>,
+        effect: Effect,
+    ) -> Result<ActionSchema, String> {
+        let ground_all = |atoms: &[AtomPattern]| {
+            atoms
+                .iter()
+                .map(|a| ground_atom(a, binding))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let name = std::iter::once(self.name.as_str())
+            .chain(self.parameters.iter().map(|(var, _)| binding[var].as_str()))
+            .collect::<Vec<_>>()
+            .join(":");
+        Ok(ActionSchema {
+            name,
+            effect,
+            preconditions: ground_all(&self.preconditions)?,
+            add_effects: ground_all(&self.add_effects)?,
+            delete_effects: ground_all(&self.delete_effects)?,
+            params: BTreeMap::new(),
+        })
+    }
+}
+
+struct Domain {
+    name: String,
+    /// Each declared type mapped to its parent type.
+    types: BTreeMap<String, String>,
+    constants: Vec<(String, String)>,
+    actions: Vec<LiftedAction>,
+}
+
+struct Problem {
+    domain: Option<String>,
+    objects: Vec<(String, String)>,
+    init: Vec<AtomPattern>,
+    goal: Vec<AtomPattern>,
+}
+
+fn parse_domain(root: &Sexpr) -> Result<Domain, String> {
+    let (kind, name, sections) = definition(root)?;
+    if kind != "domain" {
+        return Err(format!("expected a domain definition, found '{kind}'"));
+    }
+
+    let mut domain = Domain {
+        name,
+        types: BTreeMap::new(),
+        constants: Vec::new(),
+        actions: Vec::new(),
+    };
+    for section in sections {
+        let items = section.list().ok_or("expected a domain section")?;
+        match items.first().and_then(Sexpr::symbol) {
+            Some(":types") => domain.types.extend(typed_list(&items[1..])?),
+            Some(":constants") => domain.constants.extend(typed_list(&items[1..])?),
+            Some(":action") => domain.actions.push(parse_action(&items[1..])?),
+            Some(":requirements" | ":predicates") => {}
+            Some(other) => return Err(format!("unsupported domain section '{other}'")),
+            None => return Err("expected a domain section keyword".into()),
+        }
+    }
+    Ok(domain)
+}
+
+fn parse_problem(root: &Sexpr) -> Result<Problem, String> {
+    let (kind, _name, sections) = definition(root)?;
+    if kind != "problem" {
+        return Err(format!("expected a problem definition, found '{kind}'"));
+    }
+
+    let mut problem = Problem {
+        domain: None,
+        objects: Vec::new(),
+        init: Vec::new(),
+        goal: Vec::new(),
+    };
+    for section in sections {
+        let items = section.list().ok_or("expected a problem section")?;
+        match items.first().and_then(Sexpr::symbol) {
+            Some(":domain") => {
+                let name = items.get(1).and_then(Sexpr::symbol);
+                problem.domain = Some(name.ok_or(":domain needs a name")?.to_string());
+            }
+            Some(":objects") => problem.objects.extend(typed_list(&items[1..])?),
+            Some(":init") => {
+                for item in &items[1..] {
+                    problem.init.push(atom_pattern(item)?);
+                }
+            }
+            Some(":goal") => {
+                let goal = items.get(1).ok_or(":goal needs a condition")?;
+                problem.goal = conjunction(goal)?;
+            }
+            Some(":requirements") => {}
+            Some(other) => return Err(format!("unsupported problem section '{other}'")),
+            None => return Err("expected a problem section keyword".into()),
+        }
+    }
+    Ok(problem)
+}
+
+/// Split `(define (kind name) sections...)`.
+fn definition(root: &Sexpr) -> Result<(String, String, &[Sexpr]), String> {
+    let items = root.list().ok_or("expected '(define ...)'")?;
+    if items.first().and_then(Sexpr::symbol) != Some("define") {
+        return Err("expected '(define ...)'".into());
+    }
+    let header = items
+        .get(1)
+        .and_then(Sexpr::list)
+        .ok_or("expected '(domain NAME)' or '(problem NAME)'")?;
+    match header {
+        [Sexpr::Symbol(kind), Sexpr::Symbol(name)] => Ok((kind.clone(), name.clone(), &items[2..])),
+        _ => Err("expected '(domain NAME)' or '(problem NAME)'".into()),
+    }
+}
+
+/// `a b - type c` → `[(a, type), (b, type), (c, object)]`.
+fn typed_list(items: &[Sexpr]) -> Result<Vec<(String, String)>, String> {
+    let mut out = Vec::new();
+    let mut pending = Vec::new();
+    let mut iter = items.iter();
+    while let Some(item) = iter.next() {
+        let symbol = item.symbol().ok_or("typed lists hold names only")?;
+        if symbol == "-" {
+            let ty = iter
+                .next()
+                .and_then(Sexpr::symbol)
+                .ok_or("'-' must be followed by a type")?;
+            out.extend(pending.drain(..).map(|n: String| (n, ty.to_string())));
+        } else {
+            pending.push(symbol.to_string());
+        }
+    }
+    out.extend(pending.into_iter().map(|n| (n, "object".to_string())));
+    Ok(out)
+}
+
+fn parse_action(items: &[Sexpr]) -> Result<LiftedAction, String> {
+    let name = items
+        .first()
+        .and_then(Sexpr::symbol)
+        .ok_or(":action needs a name")?
+        .to_string();
+    let mut action = LiftedAction {
+        name,
+        parameters: Vec::new(),
+        preconditions: Vec::new(),
+        add_effects: Vec::new(),
+        delete_effects: Vec::new(),
+    };
+
+    let mut rest = items[1..].iter();
+    while let Some(key) = rest.next() {
+        let value = rest
+            .next()
+            .ok_or_else(|| format!("action '{}' has a key without a value", action.name))?;
+        match key.symbol() {
+            Some(":parameters") => {
+                let params = value.list().ok_or(":parameters expects a list")?;
+                action.parameters = typed_list(params)?;
+            }
+            Some(":precondition") => action.preconditions = conjunction(value)?,
+            Some(":effect") => {
+                for literal in conjuncts(value) {
+                    match literal.list() {
+                        Some([Sexpr::Symbol(not), inner]) if not == "not" => {
+                            action.delete_effects.push(atom_pattern(inner)?)
+                        }
+                        _ => action.add_effects.push(atom_pattern(literal)?),
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "unsupported key in action '{}': {key:?}",
+                    action.name
+                ));
+            }
+        }
+    }
+    Ok(action)
+}
+
+/// The members of `(and ...)`, or the condition itself.
+fn conjuncts(condition: &Sexpr) -> Vec<&Sexpr> {
+    match condition.list() {
+        Some([Sexpr::Symbol(and), rest @ ..]) if and == "and" => rest.iter().collect(),
+        Some([]) => Vec::new(),
+        _ => vec![condition],
+    }
+}
+
+/// A conjunction of positive atoms.
+fn conjunction(condition: &Sexpr) -> Result<Vec<AtomPattern>, String> {
+    conjuncts(condition).into_iter().map(atom_pattern).collect()
+}
+
+fn atom_pattern(item: &Sexpr) -> Result<AtomPattern, String> {
+    let parts = item.list().unwrap_or_default();
+    let Some(predicate) = parts.first().and_then(Sexpr::symbol) else {
+        return Err("expected an atom '(predicate args...)'".into());
+    };
+    if matches!(
+        predicate,
+        "not" | "or" | "imply" | "exists" | "forall" | "when"
+    ) {
+        return Err(format!(
+            "only positive conjunctive conditions are supported, found '{predicate}'"
+        ));
+    }
+
+    let args = parts[1..]
+        .iter()
+        .map(|p| p.symbol().map(str::to_string))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("atom ({predicate} ...) has a nested argument"))?;
+    Ok(AtomPattern {
+        predicate: predicate.to_string(),
+        args,
+    })
+}
+
+fn ground_atom(atom: &AtomPattern, binding: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut parts = vec![atom.predicate.clone()];
+    for arg in &atom.args {
+        if arg.starts_with('?') {
+            let value = binding
+                .get(arg)
+                .ok_or_else(|| format!("unbound variable '{arg}' in ({})", atom.predicate))?;
+            parts.push(value.clone());
+        } else {
+            parts.push(arg.clone());
+        }
+    }
+    Ok(parts.join(":"))
+}
+
+/// Every assignment of type-compatible objects to `parameters`.
+fn bindings(
+    parameters: &[(String, String)],
+    objects: &[(String, String)],
+    types: &BTreeMap<String, String>,
+) -> Result<Vec<BTreeMap<String, String>>, String> {
+    let mut out = vec![BTreeMap::new()];
+    for (var, ty) in parameters {
+        let candidates = objects
+            .iter()
+            .filter(|(_, obj_ty)| is_subtype(obj_ty, ty, types))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Err(format!("no objects of type '{ty}' for parameter '{var}'"));
+        }
+        out = out
+            .into_iter()
+            .flat_map(|partial| {
+                candidates.iter().map(move |name| {
+                    let mut next = partial.clone();
+                    next.insert(var.clone(), (*name).clone());
+                    next
+                })
+            })
+            .collect();
+    }
+    Ok(out)
+}
+
+fn is_subtype(ty: &str, ancestor: &str, types: &BTreeMap<String, String>) -> bool {
+    let mut current = ty;
+    // Bounded walk so a cyclic :types declaration cannot hang the import.
+    for _ in 0..=types.len() {
+        if current == ancestor || ancestor == "object" {
+            return true;
+        }
+        match types.get(current) {
+            Some(parent) => current = parent,
+            None => return false,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crashloop_actions() -> Vec<ActionSchema> {
+        vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop_detected")
+                .adds("has_pod_logs"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied")
+                .deletes("crashloop_detected")
+                .deletes("failed:scale-deployment"),
+        ]
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let actions = crashloop_actions();
+        let initial: WorldState = ["crashloop_detected", "failed:scale-deployment"]
+            .into_iter()
+            .collect();
+        let goals = vec!["remediation_applied".to_string()];
+
+        let domain = export_domain("incident", &actions);
+        let problem = export_problem("inc-1", "incident", &actions, &initial, &goals);
+        assert!(domain.contains("(not (failed scale-deployment))"));
+        assert!(domain.contains("(:constants scale-deployment)"));
+        assert!(!problem.contains(":objects"));
+        assert!(domain.contains("; effect: Mutate"));
+
+        let imported = import(&domain, &problem, &effect_annotations(&actions)).unwrap();
+        assert_eq!(imported.actions, actions);
+        assert_eq!(imported.initial, initial);
+        assert_eq!(imported.goals, goals);
+    }
+
+    #[test]
+    fn uppercase_action_names_keep_their_effects() {
+        let actions = vec![
+            ActionSchema::new("Restart-Pod", Effect::Mutate)
+                .requires("crashloop_detected")
+                .adds("remediation_applied"),
+        ];
+        let domain = export_domain("incident", &actions);
+        let problem = export_problem("inc-1", "incident", &actions, &WorldState::default(), &[]);
+
+        let imported = import(&domain, &problem, &effect_annotations(&actions)).unwrap();
+        assert_eq!(imported.actions[0].name, "restart-pod");
+        assert_eq!(imported.actions[0].effect, Effect::Mutate);
+    }
+
+    #[test]
+    fn effect_annotations_match_action_names_in_any_case() {
+        let domain = "(define (domain d) (:action Restart :parameters () \
+                      :precondition (and (crashloop_detected)) :effect (and (restarted))))";
+        let problem = "(define (problem p) (:domain d) (:init) (:goal (and (restarted))))";
+        let effects: EffectTable = [("Restart".to_string(), Effect::Mutate)].into();
+
+        let imported = import(domain, problem, &effects).unwrap();
+        assert_eq!(imported.actions[0].name, "restart");
+        assert_eq!(imported.actions[0].effect, Effect::Mutate);
+    }
+
+    #[test]
+    fn lifted_actions_are_grounded_over_typed_objects() {
+        let domain = r#"
+            ; restart any deployment
+            (define (domain K8s)
+              (:requirements :strips :typing)
+              (:types deployment statefulset - workload)
+              (:predicates (crashlooping ?w - workload) (restarted ?w - workload))
+              (:action restart
+                :parameters (?w - workload)
+                :precondition (crashlooping ?w)
+                :effect (and (restarted ?w) (not (crashlooping ?w)))))
+        "#;
+        let problem = r#"
+            (define (problem inc-2)
+              (:domain k8s)
+              (:objects checkout - deployment cache - statefulset node-1)
+              (:init (crashlooping checkout))
+              (:goal (and (restarted checkout))))
+        "#;
+        let effects: EffectTable = [("restart".to_string(), Effect::Mutate)].into();
+
+        let imported = import(domain, problem, &effects).unwrap();
+        let names: Vec<_> = imported.actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["restart:checkout", "restart:cache"]);
+        assert_eq!(
+            imported.actions[0],
+            ActionSchema::new("restart:checkout", Effect::Mutate)
+                .requires("crashlooping:checkout")
+                .adds("restarted:checkout")
+                .deletes("crashlooping:checkout")
+        );
+        assert!(imported.initial.has("crashlooping:checkout"));
+        assert_eq!(imported.goals, vec!["restarted:checkout".to_string()]);
+    }
+
+    #[test]
+    fn missing_effect_annotation_is_rejected() {
+        let actions = crashloop_actions();
+        let domain = export_domain("incident", &actions);
+        let problem = export_problem("p", "incident", &actions, &WorldState::default(), &[]);
+        let err = import(&domain, &problem, &EffectTable::new()).unwrap_err();
+        assert!(err.contains("inspect-pod-logs"));
+    }
+
+    #[test]
+    fn unsupported_constructs_are_reported() {
+        let domain = "(define (domain d) (:action a :parameters () \
+                      :precondition (not (p)) :effect (q)))";
+        let problem = "(define (problem p) (:domain d) (:init) (:goal (q)))";
+        let effects: EffectTable = [("a".to_string(), Effect::Pure)].into();
+        let err = import(domain, problem, &effects).unwrap_err();
+        assert!(err.contains("positive"));
+
+        assert!(parse_sexpr("(define (domain d)").is_err());
+    }
+}
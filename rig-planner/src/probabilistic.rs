@@ -0,0 +1,201 @@
+use crate::search::{Plan, Unreachable, actions_along, explain_unreachable, successors};
+use crate::strips::{ActionSchema, WorldState};
+use pathfinding::prelude::astar;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Fixed-point scale for expected costs, which A* needs as integers.
+const COST_SCALE: f64 = 1000.0;
+
+// ── OutcomeModel ─────────────────────────────────────────────────────────────
+
+/// Observed attempts of one action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeCounts {
+    pub successes: u32,
+    pub failures: u32,
+}
+
+/// Per-action success/failure distributions.
+///
+/// A successful attempt applies the action's effects; a failed attempt
+/// changes nothing, so the action can be retried until it succeeds.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OutcomeModel {
+    counts: BTreeMap<String, OutcomeCounts>,
+}
+
+impl OutcomeModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, action: &str, succeeded: bool) {
+        let counts = self.counts.entry(action.to_string()).or_default();
+        if succeeded {
+            counts.successes += 1;
+        } else {
+            counts.failures += 1;
+        }
+    }
+
+    pub fn counts(&self, action: &str) -> OutcomeCounts {
+        self.counts.get(action).copied().unwrap_or_default()
+    }
+
+    /// `(successes + 1) / (attempts + 1)`: one optimistic pseudo-success, so
+    /// an untried action counts as reliable and never reaches zero.
+    pub fn success_probability(&self, action: &str) -> f64 {
+        let OutcomeCounts {
+            successes,
+            failures,
+        } = self.counts(action);
+        f64::from(successes + 1) / f64::from(successes + failures + 1)
+    }
+
+    /// Expected cost of retrying `action` until it succeeds.
+    pub fn expected_cost(&self, action: &ActionSchema) -> f64 {
+        f64::from(action.cost()) / self.success_probability(&action.name)
+    }
+
+    fn weight(&self, action: &ActionSchema) -> u64 {
+        (self.expected_cost(action) * COST_SCALE).round() as u64
+    }
+}
+
+// ── Expected-cost search ─────────────────────────────────────────────────────
+
+/// A plan annotated with its outcome statistics under an [`OutcomeModel`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedPlan {
+    pub plan: Plan,
+    /// Summed expected cost, retrying each step until it succeeds.
+    pub expected_cost: f64,
+    /// Chance every step succeeds on its first attempt.
+    pub success_probability: f64,
+}
+
+impl ExpectedPlan {
+    pub fn assess(plan: Plan, model: &OutcomeModel) -> Self {
+        Self {
+            expected_cost: plan.steps.iter().map(|s| model.expected_cost(s)).sum(),
+            success_probability: plan
+                .steps
+                .iter()
+                .map(|s| model.success_probability(&s.name))
+                .product(),
+            plan,
+        }
+    }
+}
+
+/// Like [`crate::plan`], but minimizing expected cost, so an unreliable
+/// action loses to a dearer one that usually works.
+pub fn plan_expected(
+    initial: &WorldState,
+    goals: &[String],
+    actions: &[ActionSchema],
+    model: &OutcomeModel,
+) -> Result<ExpectedPlan, Unreachable> {
+    let weight = |action: &ActionSchema| model.weight(action);
+    let min_weight = actions.iter().map(weight).min().unwrap_or(0);
+
+    let found = astar(
+        initial,
+        |state| successors(state, actions, weight),
+        |state| {
+            if state.satisfies(goals) {
+                0
+            } else {
+                min_weight
+            }
+        },
+        |state| state.satisfies(goals),
+    );
+
+    match found {
+        Some((path, _)) => {
+            let steps = actions_along(&path, actions, weight);
+            let plan = Plan {
+                total_cost: steps.iter().map(ActionSchema::cost).sum(),
+                steps,
+            };
+            Ok(ExpectedPlan::assess(plan, model))
+        }
+        None => Err(explain_unreachable(initial, goals, actions)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig_effects::Effect;
+
+    fn actions() -> Vec<ActionSchema> {
+        vec![
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("crashloop_detected")
+                .adds("remediation_applied"),
+            ActionSchema::new("scale-deployment", Effect::Mutate)
+                .requires("crashloop_detected")
+                .adds("scaled"),
+            ActionSchema::new("restart-pods", Effect::Mutate)
+                .requires("scaled")
+                .adds("remediation_applied"),
+        ]
+    }
+
+    fn goals() -> Vec<String> {
+        vec!["remediation_applied".to_string()]
+    }
+
+    #[test]
+    fn untried_actions_count_as_reliable() {
+        let mut model = OutcomeModel::new();
+        assert_eq!(model.success_probability("rollback-deployment"), 1.0);
+
+        for succeeded in [true, true, false, false, false] {
+            model.record("rollback-deployment", succeeded);
+        }
+        assert_eq!(model.success_probability("rollback-deployment"), 0.5);
+        assert_eq!(model.counts("rollback-deployment").failures, 3);
+    }
+
+    #[test]
+    fn prefers_reliable_path_over_cheap_unreliable_one() {
+        let initial: WorldState = ["crashloop_detected"].into_iter().collect();
+
+        let optimistic =
+            plan_expected(&initial, &goals(), &actions(), &OutcomeModel::new()).unwrap();
+        assert_eq!(optimistic.plan.steps[0].name, "rollback-deployment");
+        assert_eq!(optimistic.success_probability, 1.0);
+
+        let mut model = OutcomeModel::new();
+        for _ in 0..4 {
+            model.record("rollback-deployment", false);
+        }
+        let planned = plan_expected(&initial, &goals(), &actions(), &model).unwrap();
+        let names: Vec<_> = planned.plan.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["scale-deployment", "restart-pods"]);
+        assert_eq!(planned.plan.total_cost, 20);
+        assert_eq!(planned.expected_cost, 20.0);
+    }
+
+    #[test]
+    fn assess_multiplies_first_attempt_odds() {
+        let mut model = OutcomeModel::new();
+        model.record("scale-deployment", false);
+        model.record("restart-pods", false);
+        model.record("restart-pods", false);
+        model.record("restart-pods", true);
+
+        let plan = Plan {
+            steps: actions()[1..].to_vec(),
+            total_cost: 20,
+        };
+        let assessed = ExpectedPlan::assess(plan, &model);
+        assert_eq!(assessed.success_probability, 0.5 * 0.5);
+        assert_eq!(assessed.expected_cost, 20.0 + 20.0);
+    }
+}
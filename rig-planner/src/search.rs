@@ -0,0 +1,257 @@
+use crate::strips::{ActionSchema, WorldState};
+use pathfinding::prelude::astar;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+// ── Plan ─────────────────────────────────────────────────────────────────────
+
+/// An ordered action sequence reaching the goal, with its summed effect cost.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub steps: Vec<ActionSchema>,
+    pub total_cost: u32,
+}
+
+// ── Unreachable ──────────────────────────────────────────────────────────────
+
+/// Why no plan exists.
+///
+/// Computed from a delete-relaxed reachability pass: anything outside the
+/// relaxed closure can never become true, whatever the action order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unreachable {
+    /// Goals that no action sequence can establish.
+    pub missing_goals: Vec<String>,
+    /// Actions that can never fire, with the preconditions that never hold.
+    pub blocked_actions: Vec<BlockedAction>,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedAction {
+    pub name: String,
+    pub missing_preconditions: Vec<String>,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+impl std::error::Error for Unreachable {}
+
+// ── Search ───────────────────────────────────────────────────────────────────
+
+/// Find the cheapest action sequence from `initial` to a state satisfying
+/// every goal, using A* with [`ActionSchema::cost`] as the step cost.
+pub fn plan(
+    initial: &WorldState,
+    goals: &[String],
+    actions: &[ActionSchema],
+) -> Result<Plan, Unreachable> {
+    // Admissible: any unsatisfied goal needs at least one more action.
+    let min_cost = actions.iter().map(ActionSchema::cost).min().unwrap_or(0);
+
+    let found = astar(
+        initial,
+        |state| successors(state, actions, ActionSchema::cost),
+        |state| if state.satisfies(goals) { 0 } else { min_cost },
+        |state| state.satisfies(goals),
+    );
+
+    match found {
+        Some((path, total_cost)) => Ok(Plan {
+            steps: actions_along(&path, actions, ActionSchema::cost),
+            total_cost,
+        }),
+        None => Err(explain_unreachable(initial, goals, actions)),
+    }
+}
+
+pub(crate) fn successors<C>(
+    state: &WorldState,
+    actions: &[ActionSchema],
+    weight: impl Fn(&ActionSchema) -> C,
+) -> Vec<(WorldState, C)> {
+    actions
+        .iter()
+        .filter(|a| a.applicable(state))
+        .map(|a| (state.apply(a), weight(a)))
+        .filter(|(next, _)| next != state)
+        .collect()
+}
+
+/// Recover the lightest action behind each state transition on a path.
+pub(crate) fn actions_along<C: Ord>(
+    path: &[WorldState],
+    actions: &[ActionSchema],
+    weight: impl Fn(&ActionSchema) -> C,
+) -> Vec<ActionSchema> {
+    path.windows(2)
+        .filter_map(|pair| {
+            actions
+                .iter()
+                .filter(|a| a.applicable(&pair[0]) && pair[0].apply(a) == pair[1])
+                .min_by_key(|a| weight(a))
+                .cloned()
+        })
+        .collect()
+}
+
+pub(crate) fn explain_unreachable(
+    initial: &WorldState,
+    goals: &[String],
+    actions: &[ActionSchema],
+) -> Unreachable {
+    let reachable = relaxed_closure(initial, actions);
+
+    let missing_goals = goals
+        .iter()
+        .filter(|g| !reachable.contains(*g))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let blocked_actions = actions
+        .iter()
+        .filter_map(|a| {
+            let missing = a
+                .preconditions
+                .iter()
+                .filter(|p| !reachable.contains(*p))
+                .cloned()
+                .collect::<Vec<_>>();
+            (!missing.is_empty()).then(|| BlockedAction {
+                name: a.name.clone(),
+                missing_preconditions: missing,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let reason = if missing_goals.is_empty() {
+        "every goal is individually reachable, but each action order deletes a required proposition"
+            .to_string()
+    } else {
+        format!("no action establishes: {}", missing_goals.join(", "))
+    };
+
+    Unreachable {
+        missing_goals,
+        blocked_actions,
+        reason,
+    }
+}
+
+/// Propositions reachable when delete effects are ignored.
+fn relaxed_closure(initial: &WorldState, actions: &[ActionSchema]) -> BTreeSet<String> {
+    let mut reachable = initial.props().clone();
+    loop {
+        let mut changed = false;
+        for action in actions {
+            if action.preconditions.iter().all(|p| reachable.contains(p)) {
+                for prop in &action.add_effects {
+                    changed |= reachable.insert(prop.clone());
+                }
+            }
+        }
+        if !changed {
+            return reachable;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig_effects::Effect;
+
+    fn goals(props: &[&str]) -> Vec<String> {
+        props.iter().map(|p| p.to_string()).collect()
+    }
+
+    fn crashloop_domain() -> Vec<ActionSchema> {
+        vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop_detected")
+                .adds("has_pod_logs"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied")
+                .deletes("crashloop_detected"),
+            ActionSchema::new("delete-namespace", Effect::Irreversible)
+                .requires("crashloop_detected")
+                .adds("remediation_applied"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("remediation_applied")
+                .adds("recovery_verified"),
+        ]
+    }
+
+    #[test]
+    fn finds_cheapest_effect_weighted_plan() {
+        let initial: WorldState = ["crashloop_detected"].into_iter().collect();
+        let plan = plan(
+            &initial,
+            &goals(&["recovery_verified"]),
+            &crashloop_domain(),
+        )
+        .unwrap();
+
+        let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["inspect-pod-logs", "rollback-deployment", "verify-recovery"]
+        );
+        assert_eq!(plan.total_cost, 2 + 10 + 2);
+    }
+
+    #[test]
+    fn satisfied_goal_yields_empty_plan() {
+        let initial: WorldState = ["recovery_verified"].into_iter().collect();
+        let plan = plan(
+            &initial,
+            &goals(&["recovery_verified"]),
+            &crashloop_domain(),
+        )
+        .unwrap();
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.total_cost, 0);
+    }
+
+    #[test]
+    fn unreachable_goal_is_explained() {
+        let initial = WorldState::default();
+        let err = plan(
+            &initial,
+            &goals(&["recovery_verified"]),
+            &crashloop_domain(),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.missing_goals, vec!["recovery_verified".to_string()]);
+        let blocked: Vec<_> = err
+            .blocked_actions
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert!(blocked.contains(&"inspect-pod-logs"));
+        assert!(blocked.contains(&"verify-recovery"));
+        assert!(err.to_string().contains("recovery_verified"));
+    }
+
+    #[test]
+    fn delete_interference_is_reported_without_missing_goals() {
+        let actions = vec![
+            ActionSchema::new("a", Effect::Mutate)
+                .adds("x")
+                .deletes("y"),
+            ActionSchema::new("b", Effect::Mutate)
+                .adds("y")
+                .deletes("x"),
+        ];
+        let err = plan(&WorldState::default(), &goals(&["x", "y"]), &actions).unwrap_err();
+        assert!(err.missing_goals.is_empty());
+        assert!(err.blocked_actions.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::search::Plan;
+use crate::strips::ActionSchema;
+use rig_effects::Effect;
+
+/// Name prefix of the approval gate steps inserted by [`shape`].
+pub const APPROVAL_GATE_PREFIX: &str = "approve:";
+
+// ── Approval gates ───────────────────────────────────────────────────────────
+
+/// A pure step that must succeed before `step` runs. Hosts decide what
+/// approval means; the executor treats it like any other step.
+pub fn approval_gate(step: &ActionSchema) -> ActionSchema {
+    ActionSchema::new(format!("{APPROVAL_GATE_PREFIX}{}", step.name), Effect::Pure)
+}
+
+/// The name of the step a gate guards, or `None` for ordinary steps.
+pub fn gated_action(step: &ActionSchema) -> Option<&str> {
+    step.name.strip_prefix(APPROVAL_GATE_PREFIX)
+}
+
+// ── Ordering ─────────────────────────────────────────────────────────────────
+
+/// Whether `later` must stay after `earlier`: it consumes something
+/// `earlier` establishes, or either one undoes what the other needs or makes.
+pub fn depends_on(later: &ActionSchema, earlier: &ActionSchema) -> bool {
+    let touches = |a: &[String], b: &[String]| a.iter().any(|p| b.contains(p));
+
+    touches(&earlier.add_effects, &later.preconditions)
+        || touches(&earlier.delete_effects, &later.preconditions)
+        || touches(&later.delete_effects, &earlier.preconditions)
+        || touches(&earlier.add_effects, &later.delete_effects)
+        || touches(&earlier.delete_effects, &later.add_effects)
+}
+
+/// Scheduling phase: reads first, then mutations, then commitment points.
+fn phase(effect: &Effect) -> u8 {
+    match effect {
+        Effect::Pure | Effect::Observe => 0,
+        Effect::Mutate => 1,
+        Effect::Irreversible => 2,
+    }
+}
+
+/// Reorder `steps` for safety without breaking any [`depends_on`] edge,
+/// then put an [`approval_gate`] in front of every non-backtrackable step.
+///
+/// Among steps whose predecessors have all been scheduled, the lowest phase
+/// goes first and ties keep their original order, so Observe steps move
+/// ahead of independent Mutate steps and Irreversible steps sink to the end.
+pub fn shape_steps(steps: &[ActionSchema]) -> Vec<ActionSchema> {
+    let mut scheduled = vec![false; steps.len()];
+    let mut out = Vec::with_capacity(steps.len());
+
+    for _ in 0..steps.len() {
+        let next = (0..steps.len())
+            .filter(|&j| !scheduled[j])
+            .filter(|&j| (0..j).all(|i| scheduled[i] || !depends_on(&steps[j], &steps[i])))
+            .min_by_key(|&j| (phase(&steps[j].effect), j))
+            .expect("earliest unscheduled step is always ready");
+        scheduled[next] = true;
+
+        let step = &steps[next];
+        if !step.effect.backtrackable() {
+            out.push(approval_gate(step));
+        }
+        out.push(step.clone());
+    }
+    out
+}
+
+/// [`shape_steps`] over a whole plan, with the cost recomputed to include gates.
+pub fn shape(plan: &Plan) -> Plan {
+    let steps = shape_steps(&plan.steps);
+    Plan {
+        total_cost: steps.iter().map(ActionSchema::cost).sum(),
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(steps: &[ActionSchema]) -> Vec<&str> {
+        steps.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn independent_observe_moves_ahead_of_mutate() {
+        let steps = vec![
+            ActionSchema::new("restart-pod", Effect::Mutate).adds("restarted"),
+            ActionSchema::new("inspect-pod-logs", Effect::Observe).adds("has_pod_logs"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("restarted")
+                .adds("recovery_verified"),
+        ];
+        assert_eq!(
+            names(&shape_steps(&steps)),
+            vec!["inspect-pod-logs", "restart-pod", "verify-recovery"]
+        );
+    }
+
+    #[test]
+    fn irreversible_step_is_delayed_and_gated() {
+        let steps = vec![
+            ActionSchema::new("drain-node", Effect::Irreversible).adds("drained"),
+            ActionSchema::new("scale-deployment", Effect::Mutate).adds("scaled"),
+            ActionSchema::new("inspect-node", Effect::Observe).adds("has_node_state"),
+        ];
+        let shaped = shape_steps(&steps);
+        assert_eq!(
+            names(&shaped),
+            vec![
+                "inspect-node",
+                "scale-deployment",
+                "approve:drain-node",
+                "drain-node"
+            ]
+        );
+        assert_eq!(gated_action(&shaped[2]), Some("drain-node"));
+        assert_eq!(gated_action(&shaped[3]), None);
+    }
+
+    #[test]
+    fn gated_step_with_dependents_keeps_every_step() {
+        let steps = vec![
+            ActionSchema::new("drain-node", Effect::Irreversible).adds("drained"),
+            ActionSchema::new("verify-drain", Effect::Observe).requires("drained"),
+        ];
+        assert_eq!(
+            names(&shape_steps(&steps)),
+            vec!["approve:drain-node", "drain-node", "verify-drain"]
+        );
+    }
+
+    #[test]
+    fn dependencies_are_never_reordered() {
+        let steps = vec![
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .adds("remediation_applied")
+                .deletes("crashloop_detected"),
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop_detected")
+                .adds("has_pod_logs"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("remediation_applied")
+                .adds("recovery_verified"),
+        ];
+        let shaped = shape(&Plan {
+            total_cost: 0,
+            steps: steps.clone(),
+        });
+        assert_eq!(names(&shaped.steps), names(&steps));
+        assert_eq!(shaped.total_cost, 10 + 2 + 2);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::strips::{ActionSchema, WorldState};
+use rig_effects::Effect;
+use serde::{Deserialize, Serialize};
+
+/// What one step would do if run from the simulated state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedStep {
+    pub name: String,
+    pub effect: Effect,
+    /// Preconditions that would not hold when the step is reached.
+    pub violated_preconditions: Vec<String>,
+    pub cost: u32,
+}
+
+/// Outcome of running a plan against a copy of the current state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Simulation {
+    pub steps: Vec<SimulatedStep>,
+    pub final_state: WorldState,
+    pub goals_met: Vec<String>,
+    pub goals_unmet: Vec<String>,
+    pub total_cost: u32,
+}
+
+impl Simulation {
+    /// Every step's preconditions held and every goal ended up true.
+    pub fn succeeds(&self) -> bool {
+        self.goals_unmet.is_empty()
+            && self
+                .steps
+                .iter()
+                .all(|s| s.violated_preconditions.is_empty())
+    }
+}
+
+/// Apply each step's modeled effects in order, starting from `initial`.
+///
+/// A step with violated preconditions is flagged but still applied, so one
+/// bad step doesn't hide what the rest of the plan would do.
+pub fn simulate(initial: &WorldState, steps: &[ActionSchema], goals: &[String]) -> Simulation {
+    let mut state = initial.clone();
+    let mut simulated = Vec::with_capacity(steps.len());
+
+    for step in steps {
+        simulated.push(SimulatedStep {
+            name: step.name.clone(),
+            effect: step.effect.clone(),
+            violated_preconditions: step
+                .preconditions
+                .iter()
+                .filter(|p| !state.has(p))
+                .cloned()
+                .collect(),
+            cost: step.cost(),
+        });
+        state = state.apply(step);
+    }
+
+    let (goals_met, goals_unmet) = goals.iter().cloned().partition(|g| state.has(g));
+    Simulation {
+        total_cost: simulated.iter().map(|s| s.cost).sum(),
+        steps: simulated,
+        final_state: state,
+        goals_met,
+        goals_unmet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runbook() -> Vec<ActionSchema> {
+        vec![
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+                .requires("crashloop_detected")
+                .adds("has_pod_logs"),
+            ActionSchema::new("rollback-deployment", Effect::Mutate)
+                .requires("has_pod_logs")
+                .adds("remediation_applied"),
+            ActionSchema::new("verify-recovery", Effect::Observe)
+                .requires("remediation_applied")
+                .adds("recovery_verified"),
+        ]
+    }
+
+    #[test]
+    fn reports_goals_reached_and_cost() {
+        let initial: WorldState = ["crashloop_detected"].into_iter().collect();
+        let sim = simulate(&initial, &runbook(), &["recovery_verified".to_string()]);
+
+        assert!(sim.succeeds());
+        assert_eq!(sim.goals_met, vec!["recovery_verified".to_string()]);
+        assert_eq!(sim.total_cost, 2 + 10 + 2);
+        assert!(sim.final_state.has("has_pod_logs"));
+        assert!(!initial.has("has_pod_logs"));
+    }
+
+    #[test]
+    fn flags_violated_preconditions_per_step() {
+        let sim = simulate(
+            &WorldState::default(),
+            &runbook(),
+            &[
+                "recovery_verified".to_string(),
+                "root_cause_known".to_string(),
+            ],
+        );
+
+        assert!(!sim.succeeds());
+        assert_eq!(
+            sim.steps[0].violated_preconditions,
+            vec!["crashloop_detected".to_string()]
+        );
+        assert!(sim.steps[1].violated_preconditions.is_empty());
+        assert_eq!(sim.goals_unmet, vec!["root_cause_known".to_string()]);
+    }
+}
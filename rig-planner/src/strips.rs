@@ -0,0 +1,149 @@
+use rig_effects::Effect;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// ── ActionSchema ─────────────────────────────────────────────────────────────
+
+/// An action the planner can schedule: what it needs, what it changes, and
+/// how severe its side effects are.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActionSchema {
+    pub name: String,
+    pub effect: Effect,
+    /// Propositions that must hold before the action can run.
+    #[serde(default)]
+    pub preconditions: Vec<String>,
+    /// Propositions made true by the action.
+    #[serde(default)]
+    pub add_effects: Vec<String>,
+    /// Propositions made false by the action. Applied before `add_effects`.
+    #[serde(default)]
+    pub delete_effects: Vec<String>,
+    /// Arguments for whatever runs the action. Planning ignores them; values
+    /// may hold placeholders for the caller to fill in.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl ActionSchema {
+    pub fn new(name: impl Into<String>, effect: Effect) -> Self {
+        Self {
+            name: name.into(),
+            effect,
+            preconditions: Vec::new(),
+            add_effects: Vec::new(),
+            delete_effects: Vec::new(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn requires(mut self, prop: impl Into<String>) -> Self {
+        self.preconditions.push(prop.into());
+        self
+    }
+
+    pub fn adds(mut self, prop: impl Into<String>) -> Self {
+        self.add_effects.push(prop.into());
+        self
+    }
+
+    pub fn deletes(mut self, prop: impl Into<String>) -> Self {
+        self.delete_effects.push(prop.into());
+        self
+    }
+
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Step cost used by search.
+    pub fn cost(&self) -> u32 {
+        self.effect.cost_weight()
+    }
+
+    pub fn applicable(&self, state: &WorldState) -> bool {
+        self.preconditions.iter().all(|p| state.has(p))
+    }
+}
+
+// ── WorldState ───────────────────────────────────────────────────────────────
+
+/// The set of propositions currently believed true. Search nodes are states.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WorldState {
+    props: BTreeSet<String>,
+}
+
+impl WorldState {
+    pub fn has(&self, prop: &str) -> bool {
+        self.props.contains(prop)
+    }
+
+    pub fn insert(&mut self, prop: impl Into<String>) -> bool {
+        self.props.insert(prop.into())
+    }
+
+    pub fn remove(&mut self, prop: &str) -> bool {
+        self.props.remove(prop)
+    }
+
+    pub fn props(&self) -> &BTreeSet<String> {
+        &self.props
+    }
+
+    pub fn satisfies(&self, goals: &[String]) -> bool {
+        goals.iter().all(|g| self.has(g))
+    }
+
+    /// The state after running `action`. Does not check preconditions.
+    pub fn apply(&self, action: &ActionSchema) -> WorldState {
+        let mut next = self.clone();
+        for prop in &action.delete_effects {
+            next.props.remove(prop);
+        }
+        for prop in &action.add_effects {
+            next.props.insert(prop.clone());
+        }
+        next
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for WorldState {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self {
+            props: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_deletes_before_adding() {
+        let state: WorldState = ["a", "b"].into_iter().collect();
+        let action = ActionSchema::new("flip", Effect::Mutate)
+            .requires("a")
+            .deletes("a")
+            .deletes("b")
+            .adds("b");
+
+        assert!(action.applicable(&state));
+        let next = state.apply(&action);
+        assert!(!next.has("a"));
+        assert!(next.has("b"));
+    }
+
+    #[test]
+    fn missing_strips_fields_deserialize_as_empty() {
+        let action: ActionSchema =
+            serde_json::from_str(r#"{"name":"inspect-pod-logs","effect":"Observe"}"#).unwrap();
+        assert_eq!(
+            action,
+            ActionSchema::new("inspect-pod-logs", Effect::Observe)
+        );
+    }
+}
@@ -1,14 +1,84 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Field, Fields, Ident, Result, parse_macro_input};
+
+/// Implement `rig_rete::Fact` for a struct with named fields. The field
+/// marked `#[fact(id)]` is the fact's id, and the one marked
+/// `#[fact(timestamp)]`, a `DateTime<Utc>`, its timestamp.
+#[proc_macro_derive(Fact, attributes(fact))]
+pub fn derive_fact(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_fact(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn expand_fact(input: &DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(input, "Fact needs named fields")),
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "Fact can only be derived for structs",
+            ));
+        }
+    };
+    let id = find_marked(fields, "id")?;
+    let timestamp = find_marked(fields, "timestamp")?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (id_field, id_ty) = (&id.ident, &id.ty);
+    let (timestamp_field, timestamp_ty) = (&timestamp.ident, &timestamp.ty);
+
+    Ok(quote! {
+        impl #impl_generics rig_rete::Fact for #name #ty_generics #where_clause {
+            type Id = #id_ty;
+
+            fn id(&self) -> &Self::Id {
+                &self.#id_field
+            }
+
+            fn timestamp(&self) -> #timestamp_ty {
+                self.#timestamp_field
+            }
+        }
+    })
+}
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+/// The one field marked `#[fact(role)]`.
+fn find_marked<'a>(fields: impl IntoIterator<Item = &'a Field>, role: &str) -> Result<&'a Field> {
+    let mut found = None;
+    for field in fields {
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("fact"))
+        {
+            let marker: Ident = attr.parse_args()?;
+            if marker != "id" && marker != "timestamp" {
+                return Err(Error::new_spanned(
+                    marker,
+                    "unsupported fact field; expected id or timestamp",
+                ));
+            }
+            if marker != role {
+                continue;
+            }
+            if found.is_some() {
+                return Err(Error::new_spanned(
+                    attr,
+                    format!("more than one #[fact({role})] field"),
+                ));
+            }
+            found = Some(field);
+        }
     }
+    found.ok_or_else(|| Error::new(Span::call_site(), format!("missing #[fact({role})] field")))
 }
@@ -159,7 +159,7 @@ pub trait ConflictStrategy<R: Rule>: Send + Sync {
     /// Select the best match from the conflict set.
     ///
     /// Returns `None` if no rule should fire.
-    fn select(&self, matches: &[RuleMatch<R>]) -> Option<&RuleMatch<R>>;
+    fn select<'a>(&self, matches: &'a [RuleMatch<R>]) -> Option<&'a RuleMatch<R>>;
 }
 
 // ── ReteNetwork ───────────────────────────────────────────────────────────────
@@ -412,7 +412,10 @@ mod tests {
     struct PriorityStrategy;
 
     impl ConflictStrategy<SimpleRule> for PriorityStrategy {
-        fn select(&self, matches: &[RuleMatch<SimpleRule>]) -> Option<&RuleMatch<SimpleRule>> {
+        fn select<'a>(
+            &self,
+            matches: &'a [RuleMatch<SimpleRule>],
+        ) -> Option<&'a RuleMatch<SimpleRule>> {
             matches.iter().max_by_key(|m| m.rule.priority())
         }
     }
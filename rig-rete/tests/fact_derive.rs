@@ -0,0 +1,24 @@
+use chrono::{DateTime, TimeZone, Utc};
+use rig_rete::Fact;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Fact)]
+struct PodRestarted {
+    #[fact(id)]
+    pod: String,
+    restarts: u32,
+    #[fact(timestamp)]
+    observed_at: DateTime<Utc>,
+}
+
+#[test]
+fn derive_reads_the_marked_fields() {
+    let observed_at = Utc.timestamp_opt(1_736_510_400, 0).unwrap();
+    let fact = PodRestarted {
+        pod: "checkout-7d9f".into(),
+        restarts: 3,
+        observed_at,
+    };
+    assert_eq!(fact.id(), "checkout-7d9f");
+    assert_eq!(fact.timestamp(), observed_at);
+    assert_eq!(fact.restarts, 3);
+}
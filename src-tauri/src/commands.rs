@@ -1,8 +1,9 @@
 use crate::state::{AppState, EscalationResponse};
+use agent_core::agent::{AgentConfig, Goal};
 use agent_core::event_log::{Event, EventType};
 use agent_core::facts::{AlertFact, AlertSource, Fact, Severity};
 use agent_core::llm;
-use agent_core::{executor, planner, rules, runbooks};
+use agent_core::{executor, planner, rules};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -39,6 +40,38 @@ pub struct PlanStepDto {
     pub status: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlanAlternativeDto {
+    pub label: String,
+    pub steps: Vec<PlanStepDto>,
+    pub total_cost: u32,
+    pub mutate_steps: usize,
+    pub irreversible_steps: usize,
+    pub expected_cost: f64,
+    pub success_probability: f64,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub cost_delta: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulationDto {
+    pub runbook: String,
+    pub steps: Vec<SimulatedStepDto>,
+    pub goals_met: Vec<String>,
+    pub goals_unmet: Vec<String>,
+    pub total_cost: u32,
+    pub succeeds: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimulatedStepDto {
+    pub name: String,
+    pub effect: String,
+    pub violated_preconditions: Vec<String>,
+    pub cost: u32,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TimelineEventDto {
     pub id: i64,
@@ -103,6 +136,18 @@ pub fn get_current_plan(state: &AppState, incident_id: String) -> Result<PlanDto
 
     let mut steps: Vec<PlanStepDto> = Vec::new();
     for e in events {
+        if matches!(e.event_type, EventType::PlanSelected) {
+            // A new plan replaces whatever the previous one had not started.
+            steps.retain(|s| s.status != "pending");
+            for (name, effect) in planned_steps(e.details.as_ref()) {
+                steps.push(PlanStepDto {
+                    name,
+                    effect,
+                    status: "pending".into(),
+                });
+            }
+            continue;
+        }
         if !matches!(
             e.event_type,
             EventType::ActionIntent | EventType::ActionResult | EventType::Escalated
@@ -141,11 +186,79 @@ pub fn get_current_plan(state: &AppState, incident_id: String) -> Result<PlanDto
     let current_step = steps
         .iter()
         .position(|step| step.status == "running")
+        .or_else(|| steps.iter().position(|step| step.status == "pending"))
         .unwrap_or_else(|| steps.len().saturating_sub(1));
 
     Ok(PlanDto { steps, current_step })
 }
 
+/// Ranked plans recorded with the most recent `PlanSelected` event that has them.
+pub fn get_plan_alternatives(
+    state: &AppState,
+    incident_id: String,
+) -> Result<Vec<PlanAlternativeDto>, String> {
+    let events = state.log.events_for_incident(&incident_id)?;
+
+    let latest = events
+        .into_iter()
+        .rev()
+        .filter(|e| matches!(e.event_type, EventType::PlanSelected))
+        .find_map(|e| e.details?.get("alternatives").cloned());
+    let Some(latest) = latest else {
+        return Ok(Vec::new());
+    };
+
+    let alternatives: Vec<rig_planner::Alternative> =
+        serde_json::from_value(latest).map_err(|e| e.to_string())?;
+    Ok(alternatives
+        .into_iter()
+        .map(|alt| PlanAlternativeDto {
+            label: alt.label,
+            steps: alt
+                .plan
+                .steps
+                .iter()
+                .map(|step| PlanStepDto {
+                    name: step.name.clone(),
+                    effect: format!("{:?}", step.effect),
+                    status: "pending".into(),
+                })
+                .collect(),
+            total_cost: alt.plan.total_cost,
+            mutate_steps: alt.mutate_steps,
+            irreversible_steps: alt.irreversible_steps,
+            expected_cost: alt.expected_cost,
+            success_probability: alt.success_probability,
+            added: alt.diff.added,
+            removed: alt.diff.removed,
+            cost_delta: alt.diff.cost_delta,
+        })
+        .collect())
+}
+
+/// `(name, effect)` of each step in `PlanSelected` details, which hold either
+/// the step list itself or an object with a `steps` list.
+fn planned_steps(details: Option<&serde_json::Value>) -> Vec<(String, String)> {
+    let Some(details) = details else {
+        return Vec::new();
+    };
+    let list = details.get("steps").unwrap_or(details);
+    let Some(list) = list.as_array() else {
+        return Vec::new();
+    };
+
+    list.iter()
+        .filter_map(|step| {
+            let name = step.get("name")?.as_str()?;
+            let effect = step
+                .get("effect")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("Observe");
+            Some((name.to_string(), effect.to_string()))
+        })
+        .collect()
+}
+
 pub fn get_tool_calls(state: &AppState, incident_id: String) -> Result<Vec<ToolCallDto>, String> {
     let events = state.log.events_for_incident(&incident_id)?;
     let mut out = Vec::new();
@@ -342,6 +455,8 @@ pub fn decide_fact_suggestion(
                 })
                 .unwrap_or_default(),
             received_at: now_string(),
+            occurred_at: None,
+            labels: Default::default(),
         });
         state.log.append(&Event {
             id: None,
@@ -394,6 +509,8 @@ pub fn upsert_alert_fact(
         title,
         tags,
         received_at: now_string(),
+        occurred_at: None,
+        labels: Default::default(),
     });
 
     state.log.append(&Event {
@@ -421,28 +538,36 @@ pub fn retract_fact(state: &AppState, incident_id: String, fact_id: String) -> R
 
 pub fn reprocess_incident(state: &AppState, incident_id: String) -> Result<(), String> {
     let events = state.log.events_for_incident(&incident_id)?;
-    let fact_map = materialize_fact_map(events);
-    let mut facts = fact_map.into_values();
+    let facts = materialize_fact_map(events)
+        .into_values()
+        .map(|(fact, _timestamp)| fact)
+        .collect::<Vec<_>>();
 
-    let Some((fact, _timestamp)) = facts.next() else {
+    let Some(fact_id) = facts.iter().find_map(|fact| match fact {
+        Fact::Alert(alert) => Some(alert.id.clone()),
+        _ => None,
+    }) else {
         return Err("no active facts for incident".into());
     };
 
-    let runbooks = vec![
-        ("crashloop_runbook", runbooks::crashloop_runbook()),
-        ("oomkill_runbook", runbooks::oomkill_runbook()),
-    ];
+    let config = AgentConfig::standard(None);
 
-    let pattern = rules::detect_pattern(&fact);
-    let Some((runbook_name, selected)) = planner::select_runbook(pattern, &runbooks) else {
+    let beliefs = rules::derive_beliefs(&facts);
+    let Some((runbook_name, runbook)) =
+        planner::select_runbook(beliefs.pattern(&fact_id), &config.runbooks)
+    else {
         return Err("no matching deterministic runbook".into());
     };
+    let goals = goal_props(&config, &planner::incident_state(&beliefs, &fact_id))?;
+    let plan = planner::plan_for_beliefs(&beliefs, &fact_id, &goals, &runbook)
+        .map_err(|unreachable| format!("runbook {runbook_name} cannot reach goal: {unreachable}"))?;
+    let selected = plan.steps;
 
     state.log.append(&Event {
         id: None,
         incident_id: incident_id.clone(),
         event_type: EventType::PlanSelected,
-        description: format!("reprocess selected runbook: {runbook_name}"),
+        description: format!("reprocess selected runbook: {runbook_name} (cost {})", plan.total_cost),
         details: serde_json::to_value(&selected).ok(),
         timestamp: now_string(),
     })?;
@@ -464,7 +589,8 @@ pub fn reprocess_incident(state: &AppState, incident_id: String) -> Result<(), S
             })?;
             Ok(())
         }
-        Err((failed_step, reason)) => {
+        Err(failure) => {
+            let failed_step = &selected[failure.index];
             state.log.append(&Event {
                 id: None,
                 incident_id,
@@ -474,7 +600,7 @@ pub fn reprocess_incident(state: &AppState, incident_id: String) -> Result<(), S
                     "name": failed_step.name,
                     "effect": format!("{:?}", failed_step.effect),
                     "status": "failed",
-                    "reason": reason
+                    "reason": failure.reason
                 })),
                 timestamp: now_string(),
             })?;
@@ -483,6 +609,47 @@ pub fn reprocess_incident(state: &AppState, incident_id: String) -> Result<(), S
     }
 }
 
+/// Dry-run `runbook` against the incident's current beliefs. Nothing is
+/// executed and nothing is appended to the event log.
+pub fn simulate_plan(
+    state: &AppState,
+    incident_id: String,
+    runbook: String,
+) -> Result<SimulationDto, String> {
+    let config = AgentConfig::standard(None);
+    let Some((_, steps)) = config.runbooks.iter().find(|(name, _)| *name == runbook) else {
+        return Err(format!("unknown runbook: {runbook}"));
+    };
+
+    let events = state.log.events_for_incident(&incident_id)?;
+    let fact_map = materialize_fact_map(events);
+    let facts: Vec<Fact> = fact_map.values().map(|(fact, _)| fact.clone()).collect();
+    let beliefs = rules::derive_beliefs(&facts);
+    let initial: rig_planner::WorldState = fact_map
+        .keys()
+        .flat_map(|fact_id| beliefs.propositions_for(fact_id))
+        .collect();
+
+    let simulation = rig_planner::simulate(&initial, steps, &goal_props(&config, &initial)?);
+    Ok(SimulationDto {
+        runbook,
+        succeeds: simulation.succeeds(),
+        steps: simulation
+            .steps
+            .into_iter()
+            .map(|step| SimulatedStepDto {
+                name: step.name,
+                effect: format!("{:?}", step.effect),
+                violated_preconditions: step.violated_preconditions,
+                cost: step.cost,
+            })
+            .collect(),
+        goals_met: simulation.goals_met,
+        goals_unmet: simulation.goals_unmet,
+        total_cost: simulation.total_cost,
+    })
+}
+
 #[cfg(feature = "tauri-app")]
 #[tauri::command(rename_all = "camelCase")]
 pub fn list_incidents_cmd(state: tauri::State<'_, AppState>) -> Result<Vec<IncidentDto>, String> {
@@ -516,6 +683,15 @@ pub fn get_current_plan_cmd(
     get_current_plan(&state, incident_id)
 }
 
+#[cfg(feature = "tauri-app")]
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_plan_alternatives_cmd(
+    state: tauri::State<'_, AppState>,
+    incident_id: String,
+) -> Result<Vec<PlanAlternativeDto>, String> {
+    get_plan_alternatives(&state, incident_id)
+}
+
 #[cfg(feature = "tauri-app")]
 #[tauri::command(rename_all = "camelCase")]
 pub fn get_tool_calls_cmd(
@@ -576,6 +752,16 @@ pub fn reprocess_incident_cmd(
     reprocess_incident(&state, incident_id)
 }
 
+#[cfg(feature = "tauri-app")]
+#[tauri::command(rename_all = "camelCase")]
+pub fn simulate_plan_cmd(
+    state: tauri::State<'_, AppState>,
+    incident_id: String,
+    runbook: String,
+) -> Result<SimulationDto, String> {
+    simulate_plan(&state, incident_id, runbook)
+}
+
 #[cfg(feature = "tauri-app")]
 #[tauri::command(rename_all = "camelCase")]
 pub fn generate_fact_suggestions_cmd(
@@ -631,19 +817,20 @@ fn summarize_incident(state: &AppState, incident_id: String) -> Result<IncidentD
             EventType::PlanSelected => {
                 current_phase = "planning".into();
             }
+            EventType::IntentionAdopted
+            | EventType::IntentionDropped
+            | EventType::IntentionPreempted
+            | EventType::PlanRevised => {}
             EventType::ActionIntent | EventType::ActionResult => {
                 current_phase = "executing".into();
             }
             EventType::FactAsserted => {
                 current_phase = "matching".into();
                 if let Some(details) = &event.details {
-                    if let Ok(fact) = serde_json::from_value::<Fact>(details.clone()) {
-                        match fact {
-                            Fact::Alert(alert) => {
-                                title = alert.title;
-                                severity = severity_to_string(alert.severity);
-                            }
-                        }
+                    if let Ok(Fact::Alert(alert)) = serde_json::from_value::<Fact>(details.clone())
+                    {
+                        title = alert.title;
+                        severity = severity_to_string(alert.severity);
                     }
                 }
             }
@@ -679,6 +866,71 @@ fn fact_to_dto(fact: Fact, timestamp: String) -> FactDto {
             tags: alert.tags,
             timestamp,
         },
+        Fact::Pod(pod) => FactDto {
+            fact_id: pod.id,
+            fact_type: "Pod".into(),
+            summary: format!(
+                "{}/{} {}{}, {} restarts{}",
+                pod.namespace,
+                pod.name,
+                pod.phase,
+                if pod.ready { "" } else { " (not ready)" },
+                pod.restarts,
+                pod.reason.map(|r| format!(": {r}")).unwrap_or_default()
+            ),
+            severity: String::new(),
+            tags: vec![format!("service:{}", pod.service)],
+            timestamp,
+        },
+        Fact::Deploy(deploy) => FactDto {
+            fact_id: deploy.id,
+            fact_type: "Deploy".into(),
+            summary: format!(
+                "{}/{} {}: {}/{} replicas available",
+                deploy.namespace,
+                deploy.name,
+                deploy.version,
+                deploy.available_replicas,
+                deploy.replicas
+            ),
+            severity: String::new(),
+            tags: vec![format!("service:{}", deploy.service)],
+            timestamp,
+        },
+        Fact::Metric(metric) => FactDto {
+            fact_id: metric.id.clone(),
+            fact_type: "Metric".into(),
+            summary: format!(
+                "{} = {}{}{}",
+                metric.name,
+                metric.value,
+                metric.unit.as_deref().unwrap_or_default(),
+                if metric.breached() {
+                    " (above threshold)"
+                } else {
+                    ""
+                }
+            ),
+            severity: String::new(),
+            tags: vec![format!("service:{}", metric.service)],
+            timestamp,
+        },
+        Fact::Log(log) => FactDto {
+            fact_id: log.id,
+            fact_type: "Log".into(),
+            summary: format!("{}: {}", log.level, log.message),
+            severity: String::new(),
+            tags: vec![format!("service:{}", log.service)],
+            timestamp,
+        },
+        Fact::Change(change) => FactDto {
+            fact_id: change.id,
+            fact_type: "Change".into(),
+            summary: format!("{}: {}", change.kind, change.description),
+            severity: String::new(),
+            tags: vec![format!("service:{}", change.service)],
+            timestamp,
+        },
     }
 }
 
@@ -707,10 +959,7 @@ fn materialize_fact_map(events: Vec<Event>) -> BTreeMap<String, (Fact, String)>
                 let ts = event.timestamp;
                 if let Some(value) = event.details {
                     if let Ok(fact) = serde_json::from_value::<Fact>(value) {
-                        let fact_id = match &fact {
-                            Fact::Alert(alert) => alert.id.clone(),
-                        };
-                        current.insert(fact_id, (fact, ts));
+                        current.insert(fact.fact_id().to_string(), (fact, ts));
                     }
                 }
             }
@@ -745,6 +994,17 @@ pub fn append_escalation_response_event(
     Ok(())
 }
 
+/// The propositions of the agent's most pressing goal that applies in
+/// `state`, so reprocessing and dry runs aim where the agent would.
+fn goal_props(
+    config: &AgentConfig,
+    state: &rig_planner::WorldState,
+) -> Result<Vec<String>, String> {
+    Goal::select(&config.goals, state)
+        .map(Goal::propositions)
+        .ok_or_else(|| "no goal applies to the incident".into())
+}
+
 fn now_string() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -820,6 +1080,8 @@ mod tests {
             title: "db latency high".into(),
             tags: vec!["db".into()],
             received_at: "10".into(),
+            occurred_at: None,
+            labels: Default::default(),
         });
 
         log.append(&Event {
@@ -891,6 +1153,98 @@ mod tests {
         assert_eq!(plan.steps[0].status, "done");
     }
 
+    #[test]
+    fn get_current_plan_lists_pending_steps_and_gates() {
+        let log = EventLog::open(&db_path("get-current-plan-gates")).expect("open");
+        let steps = serde_json::json!([
+            {"name": "inspect-node", "effect": "Observe"},
+            {"name": "approve:drain-node", "effect": "Pure"},
+            {"name": "drain-node", "effect": "Irreversible"}
+        ]);
+
+        log.append(&Event {
+            id: None,
+            incident_id: "inc-gates".into(),
+            event_type: EventType::PlanSelected,
+            description: "plan".into(),
+            details: Some(steps),
+            timestamp: "20".into(),
+        })
+        .expect("append plan");
+        log.append(&Event {
+            id: None,
+            incident_id: "inc-gates".into(),
+            event_type: EventType::ActionResult,
+            description: "result".into(),
+            details: Some(serde_json::json!({
+                "name": "inspect-node",
+                "effect": "Observe",
+                "status": "done"
+            })),
+            timestamp: "21".into(),
+        })
+        .expect("append result");
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let state = AppState {
+            log: Arc::new(log),
+            decision_tx: tx,
+        };
+
+        let plan = get_current_plan(&state, "inc-gates".into()).expect("plan");
+        let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["inspect-node", "approve:drain-node", "drain-node"]);
+        assert_eq!(plan.steps[0].status, "done");
+        assert_eq!(plan.steps[1].status, "pending");
+        assert_eq!(plan.steps[1].effect, "Pure");
+        assert_eq!(plan.current_step, 1);
+    }
+
+    #[test]
+    fn get_plan_alternatives_reads_latest_ranked_plans() {
+        use rig_planner::{ActionSchema, Plan};
+
+        let log = EventLog::open(&db_path("plan-alternatives")).expect("open");
+        let best = Plan {
+            steps: vec![ActionSchema::new("rollback-deployment", rig_effects::Effect::Mutate)],
+            total_cost: 10,
+        };
+        let other = Plan {
+            steps: vec![ActionSchema::new("delete-pod", rig_effects::Effect::Irreversible)],
+            total_cost: 100,
+        };
+
+        log.append(&Event {
+            id: None,
+            incident_id: "inc-alt".into(),
+            event_type: EventType::PlanSelected,
+            description: "plan".into(),
+            details: Some(serde_json::json!({
+                "steps": best.steps,
+                "alternatives": rig_planner::compare(
+                    &[best.clone(), other],
+                    &rig_planner::OutcomeModel::new()
+                )
+            })),
+            timestamp: "40".into(),
+        })
+        .expect("append plan");
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let state = AppState {
+            log: Arc::new(log),
+            decision_tx: tx,
+        };
+
+        let alternatives = get_plan_alternatives(&state, "inc-alt".into()).expect("alternatives");
+        assert_eq!(alternatives.len(), 2);
+        assert_eq!(alternatives[1].label, "B");
+        assert_eq!(alternatives[1].steps[0].effect, "Irreversible");
+        assert_eq!(alternatives[1].irreversible_steps, 1);
+        assert_eq!(alternatives[1].removed, vec!["rollback-deployment"]);
+        assert_eq!(alternatives[1].cost_delta, 90);
+    }
+
     #[test]
     fn get_tool_calls_reads_intent_and_result() {
         let log = EventLog::open(&db_path("get-tool-calls")).expect("open");
@@ -986,6 +1340,45 @@ mod tests {
         assert!(timeline.iter().any(|e| e.event_type == "Resolved"));
     }
 
+    #[test]
+    fn simulate_plan_reports_outcome_without_executing() {
+        let log = EventLog::open(&db_path("simulate-plan")).expect("open");
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let state = AppState {
+            log: Arc::new(log),
+            decision_tx: tx,
+        };
+
+        upsert_alert_fact(
+            &state,
+            "inc-sim".into(),
+            "inc-sim".into(),
+            "Pod crashlooping".into(),
+            "high".into(),
+            vec![],
+        )
+        .expect("upsert");
+        let before = get_timeline(&state, "inc-sim".into()).expect("timeline").len();
+
+        let crashloop =
+            simulate_plan(&state, "inc-sim".into(), "crashloop_runbook".into()).expect("simulate");
+        assert!(crashloop.succeeds);
+        assert_eq!(crashloop.goals_met, vec!["recovery_verified"]);
+        assert_eq!(crashloop.total_cost, 2 + 10 + 2);
+
+        let oomkill =
+            simulate_plan(&state, "inc-sim".into(), "oomkill_runbook".into()).expect("simulate");
+        assert!(!oomkill.succeeds);
+        assert_eq!(
+            oomkill.steps[0].violated_preconditions,
+            vec!["oomkill_detected"]
+        );
+
+        assert!(simulate_plan(&state, "inc-sim".into(), "nope".into()).is_err());
+        let after = get_timeline(&state, "inc-sim".into()).expect("timeline").len();
+        assert_eq!(before, after);
+    }
+
     #[test]
     fn suggestion_lifecycle_tracks_pending_queue() {
         let log = EventLog::open(&db_path("suggestion-lifecycle")).expect("open");
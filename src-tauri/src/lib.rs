@@ -52,12 +52,14 @@ pub fn run_tauri() {
             commands::get_beliefs_cmd,
             commands::get_timeline_cmd,
             commands::get_current_plan_cmd,
+            commands::get_plan_alternatives_cmd,
             commands::get_tool_calls_cmd,
             commands::get_suggested_facts_cmd,
             commands::respond_to_escalation_cmd,
             commands::upsert_alert_fact_cmd,
             commands::retract_fact_cmd,
             commands::reprocess_incident_cmd,
+            commands::simulate_plan_cmd,
             commands::generate_fact_suggestions_cmd,
             commands::decide_fact_suggestion_cmd
         ])
@@ -107,6 +109,8 @@ fn seed_demo_data(state: &AppState) -> Result<(), String> {
         title: "Pod crashlooping".into(),
         tags: vec!["demo".into()],
         received_at: "1700000000".into(),
+        occurred_at: None,
+        labels: Default::default(),
     });
 
     state.log.append(&Event {
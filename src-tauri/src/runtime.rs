@@ -38,22 +38,7 @@ pub fn start_tauri_runtime(state: &AppState, channels: RuntimeChannels, app: tau
 
     let log_for_agent = (*state.log).clone();
     std::thread::spawn(move || {
-        let config = agent_core::agent::AgentConfig {
-            max_replan_attempts: 3,
-            runbooks: vec![
-                ("crashloop_runbook", agent_core::runbooks::crashloop_runbook()),
-                ("oomkill_runbook", agent_core::runbooks::oomkill_runbook()),
-            ],
-            all_actions: vec![
-                agent_core::runbooks::crashloop_runbook(),
-                agent_core::runbooks::oomkill_runbook(),
-            ]
-            .into_iter()
-            .flatten()
-            .collect(),
-            goal_props: vec!["recovery_verified".into()],
-            llm: build_llm_config_from_env(),
-        };
+        let config = agent_core::agent::AgentConfig::standard(build_llm_config_from_env());
 
         agent_core::agent::run_agent(webhook_stream, config, log_for_agent, escalation_tx, |_action| {
             Ok(serde_json::json!({"status": "ok"}))